@@ -0,0 +1,37 @@
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use sitemap::fast_reader::FastSiteMapReader;
+use sitemap::reader::SiteMapReader;
+use sitemap::structs::{ChangeFreq, UrlEntry};
+use sitemap::writer::SiteMapWriter;
+use chrono::DateTime;
+
+fn document(count: usize) -> Vec<u8> {
+    let lastmod = DateTime::parse_from_rfc3339("2016-07-08T09:10:11+00:00").unwrap();
+    let urls = (0..count).map(|number| {
+        UrlEntry::builder()
+            .loc(format!("https://www.example.com/catalog/item{}.html", number))
+            .lastmod(lastmod)
+            .changefreq(ChangeFreq::Weekly)
+            .priority(0.5)
+            .build()
+            .unwrap()
+    });
+    let (document, _) = SiteMapWriter::new(Vec::new()).write_urlset_from_iter(urls).unwrap();
+    document
+}
+
+fn read(c: &mut Criterion) {
+    let document = document(10_000);
+    let mut group = c.benchmark_group("read");
+    group.throughput(Throughput::Bytes(document.len() as u64));
+    group.bench_function("reader", |b| {
+        b.iter(|| SiteMapReader::new(document.as_slice()).count())
+    });
+    group.bench_function("fast_reader", |b| {
+        b.iter(|| FastSiteMapReader::new(document.as_slice()).count())
+    });
+    group.finish();
+}
+
+criterion_group!(benches, read);
+criterion_main!(benches);
@@ -0,0 +1,79 @@
+//! XML backends, selected by cargo features.
+//!
+//! Every backend provides `EventReader` returning `Event` items and `XmlWriter`,
+//! and converts its errors into `crate::Error`, so no backend type is part of the public API.
+//! Text is escaped the same way by every backend.
+//! Document type declarations are rejected by every backend, so entities
+//! declared by the document are never expanded.
+//! `quick-xml` backend takes precedence when both features are enabled.
+
+#[cfg(not(any(feature = "xml-rs", feature = "quick-xml")))]
+compile_error!("either `xml-rs` or `quick-xml` feature should be enabled");
+
+#[cfg(feature = "quick-xml")]
+pub(crate) mod quick;
+#[cfg(feature = "quick-xml")]
+pub(crate) use self::quick::{EventReader, XmlWriter};
+
+#[cfg(all(feature = "xml-rs", not(feature = "quick-xml")))]
+mod xmlrs;
+#[cfg(all(feature = "xml-rs", not(feature = "quick-xml")))]
+pub(crate) use self::xmlrs::{EventReader, XmlWriter};
+
+use std::borrow::Cow;
+
+fn escape(text: &str, attribute: bool) -> Cow<'_, str> {
+    let special = |c: char| match c {
+        '&' | '<' | '>' | '"' | '\'' => true,
+        '\n' | '\r' => attribute,
+        _ => false,
+    };
+    if !text.contains(special) {
+        return Cow::Borrowed(text);
+    }
+    let mut escaped = String::with_capacity(text.len() + 16);
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            '\n' if attribute => escaped.push_str("&#xA;"),
+            '\r' if attribute => escaped.push_str("&#xD;"),
+            c => escaped.push(c),
+        }
+    }
+    return Cow::Owned(escaped);
+}
+
+/// Escapes character data, replacing all five XML special characters with entities.
+pub(crate) fn escape_pcdata(text: &str) -> Cow<'_, str> {
+    return escape(text, false);
+}
+
+/// Escapes attribute value, replacing line breaks in addition to XML special characters.
+pub(crate) fn escape_attribute(text: &str) -> Cow<'_, str> {
+    return escape(text, true);
+}
+
+/// Syntax error message of documents with document type declaration.
+pub(crate) const DOCTYPE_NOT_SUPPORTED: &str = "document type declarations are not supported";
+
+/// Event of the read document.
+#[derive(Clone,Debug,PartialEq,Eq)]
+pub(crate) enum Event {
+    /// Start of element with the local name.
+    Start {
+        /// Local name of the element.
+        name: String,
+        /// Namespace of the element.
+        namespace: Option<String>,
+        /// Number of attributes, namespace declarations excluded.
+        attributes: usize,
+    },
+    /// End of element.
+    End,
+    /// Text or CDATA content, whitespace only text is skipped.
+    Text(String),
+}
@@ -0,0 +1,357 @@
+//! Backend based on quick-xml.
+//!
+//! Markup is formatted the same way as with xml-rs backend.
+use std::io::{self, BufRead, Read, Write};
+use quick_xml::events::{BytesStart, Event as ReadEvent};
+use quick_xml::name::ResolveResult;
+use quick_xml::NsReader;
+use crate::Error;
+use crate::errors::read_io_error;
+use crate::writer::XmlVersion;
+use super::{Event, DOCTYPE_NOT_SUPPORTED, escape_attribute, escape_pcdata};
+
+/// Counts lines and columns of the consumed input.
+pub(crate) struct Position<R: BufRead> {
+    pub(crate) inner: R,
+    line: u64,
+    column: u64,
+}
+
+impl<R: BufRead> Position<R> {
+    pub(crate) fn new(inner: R) -> Position<R> {
+        Position { inner, line: 0, column: 0 }
+    }
+
+    /// Returns syntax error at the current position.
+    pub(crate) fn syntax_error<M: ToString>(&self, message: M) -> Error {
+        return Error::XmlSyntax {
+            line: self.line + 1,
+            column: self.column + 1,
+            message: message.to_string(),
+        };
+    }
+
+    /// Converts error of the parser.
+    pub(crate) fn read_error(&self, error: quick_xml::Error) -> Error {
+        if let quick_xml::Error::Io(error) = error {
+            return read_io_error(&error);
+        }
+        return self.syntax_error(error);
+    }
+}
+
+fn advance(line: &mut u64, column: &mut u64, data: &[u8]) {
+    for byte in data {
+        if *byte == b'\n' {
+            *line += 1;
+            *column = 0;
+        } else if *byte & 0xc0 != 0x80 {
+            *column += 1;
+        }
+    }
+}
+
+impl<R: BufRead> Read for Position<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        advance(&mut self.line, &mut self.column, &buf[..read]);
+        return Ok(read);
+    }
+}
+
+impl<R: BufRead> BufRead for Position<R> {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        return self.inner.fill_buf();
+    }
+
+    fn consume(&mut self, amt: usize) {
+        // The buffer is filled already, so it is returned again without reading.
+        if let Ok(buf) = self.inner.fill_buf() {
+            advance(&mut self.line, &mut self.column, &buf[..amt.min(buf.len())]);
+        }
+        self.inner.consume(amt);
+    }
+}
+
+/// Returns number of attributes of the element, namespace declarations excluded.
+pub(crate) fn count_attributes(element: &BytesStart<'_>) -> usize {
+    return element
+        .attributes()
+        .with_checks(false)
+        .filter_map(Result::ok)
+        .filter(|attribute| attribute.key.as_namespace_binding().is_none())
+        .count();
+}
+
+/// Returns namespace the element is bound to, unknown prefixes are ignored.
+pub(crate) fn namespace(resolved: ResolveResult<'_>) -> Option<String> {
+    match resolved {
+        ResolveResult::Bound(namespace) => Some(String::from_utf8_lossy(namespace.as_ref()).into_owned()),
+        _ => None,
+    }
+}
+
+/// Reads events of the document.
+pub(crate) struct EventReader<R: BufRead> {
+    parser: NsReader<Position<R>>,
+    buf: Vec<u8>,
+    depth: usize,
+    root: bool,
+    finished: bool,
+}
+
+impl<R: BufRead> EventReader<R> {
+    pub(crate) fn new(source: R) -> EventReader<R> {
+        let mut parser = NsReader::from_reader(Position::new(source));
+        parser.config_mut().expand_empty_elements = true;
+        EventReader {
+            parser,
+            buf: Vec::new(),
+            depth: 0,
+            root: false,
+            finished: false,
+        }
+    }
+
+    pub(crate) fn get_mut(&mut self) -> &mut R {
+        return &mut self.parser.get_mut().inner;
+    }
+
+    fn next_event(&mut self) -> Result<Option<Event>, Error> {
+        loop {
+            self.buf.clear();
+            match self.parser.read_event_into(&mut self.buf) {
+                Ok(ReadEvent::Start(element)) => {
+                    self.depth += 1;
+                    self.root = true;
+                    let (resolved, name) = self.parser.resolve_element(element.name());
+                    let name = String::from_utf8_lossy(name.as_ref()).into_owned();
+                    let namespace = namespace(resolved);
+                    let attributes = count_attributes(&element);
+                    return Ok(Some(Event::Start { name, namespace, attributes }));
+                }
+                Ok(ReadEvent::End(_)) => {
+                    self.depth -= 1;
+                    return Ok(Some(Event::End));
+                }
+                Ok(ReadEvent::Text(text)) => {
+                    let position = self.parser.get_ref();
+                    let text = text.unescape().map_err(|error| position.read_error(error))?;
+                    if !text.trim().is_empty() {
+                        return Ok(Some(Event::Text(text.into_owned())));
+                    }
+                }
+                Ok(ReadEvent::CData(data)) => {
+                    let position = self.parser.get_ref();
+                    let data = data.decode().map_err(|error| position.read_error(error.into()))?;
+                    return Ok(Some(Event::Text(data.into_owned())));
+                }
+                Ok(ReadEvent::DocType(_)) => {
+                    return Err(self.parser.get_ref().syntax_error(DOCTYPE_NOT_SUPPORTED));
+                }
+                Ok(ReadEvent::Eof) => {
+                    if self.depth > 0 || !self.root {
+                        return Err(self.parser.get_ref().syntax_error("unexpected end of document"));
+                    }
+                    return Ok(None);
+                }
+                Ok(_) => {}
+                Err(error) => {
+                    return Err(self.parser.get_ref().read_error(error));
+                }
+            }
+        }
+    }
+}
+
+impl<R: BufRead> Iterator for EventReader<R> {
+    type Item = Result<Event, Error>;
+    fn next(&mut self) -> Option<Result<Event, Error>> {
+        if self.finished {
+            return None;
+        }
+        let event = self.next_event();
+        if !matches!(event, Ok(Some(_))) {
+            self.finished = true;
+        }
+        return event.transpose();
+    }
+}
+
+#[derive(Clone,Copy,PartialEq,Eq)]
+enum Wrote {
+    Nothing,
+    Markup,
+    Text,
+}
+
+/// Writes markup of the document.
+pub(crate) struct XmlWriter<W: Write> {
+    out: W,
+    indent: Option<String>,
+    line_separator: String,
+    level: usize,
+    indent_stack: Vec<Wrote>,
+    // Closing bracket of the last start tag is pending until its content is known.
+    pending_start: bool,
+}
+
+impl<W: Write> XmlWriter<W> {
+    pub(crate) fn new(out: W, indent: Option<&str>, line_separator: &str) -> XmlWriter<W> {
+        XmlWriter {
+            out,
+            indent: indent.map(str::to_string),
+            line_separator: line_separator.to_string(),
+            level: 0,
+            indent_stack: vec![Wrote::Nothing],
+            pending_start: false,
+        }
+    }
+
+    fn last_wrote(&self) -> Option<Wrote> {
+        return self.indent_stack.last().copied();
+    }
+
+    fn set_wrote(&mut self, wrote: Wrote) {
+        if let Some(last) = self.indent_stack.last_mut() {
+            *last = wrote;
+        }
+    }
+
+    fn write_newline(&mut self, level: usize) -> Result<(), Error> {
+        self.out.write_all(self.line_separator.as_bytes())?;
+        if let Some(ref indent) = self.indent {
+            for _ in 0..level {
+                self.out.write_all(indent.as_bytes())?;
+            }
+        }
+        return Ok(());
+    }
+
+    fn close_pending_start(&mut self) -> Result<(), Error> {
+        if self.pending_start {
+            self.pending_start = false;
+            self.out.write_all(b">")?;
+        }
+        return Ok(());
+    }
+
+    fn before_markup(&mut self) -> Result<(), Error> {
+        let indent = match self.indent {
+            Some(ref indent) => indent.len(),
+            None => return Ok(()),
+        };
+        let last = self.last_wrote();
+        if last != Some(Wrote::Text) && (self.level > 0 || last == Some(Wrote::Markup)) {
+            self.write_newline(self.level)?;
+            if self.level > 0 && indent > 0 {
+                self.set_wrote(Wrote::Markup);
+            }
+        }
+        return Ok(());
+    }
+
+    pub(crate) fn start_document(
+        &mut self,
+        version: XmlVersion,
+        encoding: &str,
+        standalone: Option<bool>,
+    ) -> Result<(), Error> {
+        self.before_markup()?;
+        let version = match version {
+            XmlVersion::Version10 => "1.0",
+            XmlVersion::Version11 => "1.1",
+        };
+        write!(self.out, "<?xml version=\"{}\" encoding=\"{}\"", version, encoding)?;
+        if let Some(standalone) = standalone {
+            write!(self.out, " standalone=\"{}\"", if standalone { "yes" } else { "no" })?;
+        }
+        self.out.write_all(b"?>")?;
+        self.set_wrote(Wrote::Markup);
+        return Ok(());
+    }
+
+    pub(crate) fn processing_instruction(&mut self, name: &str, data: &str) -> Result<(), Error> {
+        self.close_pending_start()?;
+        self.before_markup()?;
+        write!(self.out, "<?{} {}?>", name, data)?;
+        self.set_wrote(Wrote::Markup);
+        return Ok(());
+    }
+
+    pub(crate) fn comment(&mut self, text: &str) -> Result<(), Error> {
+        self.close_pending_start()?;
+        self.before_markup()?;
+        self.out.write_all(b"<!--")?;
+        if !text.starts_with(char::is_whitespace) {
+            self.out.write_all(b" ")?;
+        }
+        for chunk in text.split_inclusive("--") {
+            match chunk.strip_suffix("--") {
+                Some(chunk) => {
+                    self.out.write_all(chunk.as_bytes())?;
+                    self.out.write_all(b"- ")?;
+                }
+                None => self.out.write_all(chunk.as_bytes())?,
+            }
+        }
+        if !text.ends_with(char::is_whitespace) {
+            self.out.write_all(b" ")?;
+        }
+        self.out.write_all(b"-->")?;
+        self.set_wrote(Wrote::Markup);
+        return Ok(());
+    }
+
+    pub(crate) fn start_element(&mut self, name: &str, namespace: Option<&str>) -> Result<(), Error> {
+        self.close_pending_start()?;
+        self.before_markup()?;
+        self.indent_stack.push(Wrote::Nothing);
+        write!(self.out, "<{}", name)?;
+        if let Some(namespace) = namespace.filter(|namespace| !namespace.is_empty()) {
+            write!(self.out, " xmlns=\"{}\"", escape_attribute(namespace))?;
+        }
+        self.set_wrote(Wrote::Markup);
+        self.level += 1;
+        self.pending_start = true;
+        return Ok(());
+    }
+
+    pub(crate) fn characters(&mut self, text: &str) -> Result<(), Error> {
+        self.close_pending_start()?;
+        self.out.write_all(escape_pcdata(text).as_bytes())?;
+        self.set_wrote(Wrote::Text);
+        return Ok(());
+    }
+
+    pub(crate) fn end_element(&mut self, name: &str) -> Result<(), Error> {
+        if self.pending_start {
+            self.pending_start = false;
+            self.out.write_all(b" />")?;
+        } else {
+            let last = self.last_wrote();
+            if self.indent.is_some() && self.level > 0 && last == Some(Wrote::Markup) {
+                self.write_newline(self.level - 1)?;
+            }
+            write!(self.out, "</{}>", name)?;
+        }
+        if self.level > 0 {
+            self.level -= 1;
+            self.indent_stack.pop();
+        }
+        self.set_wrote(Wrote::Markup);
+        return Ok(());
+    }
+
+    pub(crate) fn inner_ref(&self) -> &W {
+        return &self.out;
+    }
+
+    pub(crate) fn inner_mut(&mut self) -> &mut W {
+        return &mut self.out;
+    }
+
+    pub(crate) fn into_inner(self) -> W {
+        return self.out;
+    }
+}
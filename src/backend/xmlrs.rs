@@ -0,0 +1,225 @@
+//! Backend based on xml-rs.
+use std::io::{self, BufRead, Read, Write};
+use xml::common::Position;
+use xml::reader::{self, Events, XmlEvent as ReadEvent};
+use xml::writer::{self, EventWriter, EmitterConfig, XmlEvent as WriteEvent};
+use crate::Error;
+use crate::errors::read_io_error;
+use crate::writer::XmlVersion;
+use super::{Event, DOCTYPE_NOT_SUPPORTED, escape_pcdata};
+
+fn read_error(err: reader::Error) -> Error {
+    let position = err.position();
+    let message = match err.kind() {
+        reader::ErrorKind::Io(err) => return read_io_error(err),
+        reader::ErrorKind::Syntax(message) => message.to_string(),
+        reader::ErrorKind::Utf8(err) => err.to_string(),
+        reader::ErrorKind::UnexpectedEof => "unexpected end of document".to_string(),
+    };
+    Error::XmlSyntax {
+        line: position.row + 1,
+        column: position.column + 1,
+        message,
+    }
+}
+
+fn write_error(err: writer::Error) -> Error {
+    match err {
+        writer::Error::Io(err) => Error::Io(err),
+        // The writer emits well-formed events, other errors are not expected.
+        err => Error::Io(io::Error::other(err.to_string())),
+    }
+}
+
+/// Markup of the prolog, seen by `Prolog` before the root element.
+#[derive(Clone,Copy,PartialEq,Eq)]
+enum Markup {
+    Outside,
+    Open,
+    Instruction,
+    InstructionEnd,
+    Declaration,
+    CommentStart,
+    Comment,
+    CommentDash,
+    CommentEnd,
+    Root,
+}
+
+/// Rejects document type declaration before xml-rs expands entities declared by it.
+struct Prolog<R: BufRead> {
+    inner: R,
+    markup: Markup,
+    line: u64,
+    column: u64,
+}
+
+impl<R: BufRead> Prolog<R> {
+    fn new(inner: R) -> Prolog<R> {
+        Prolog { inner, markup: Markup::Outside, line: 0, column: 0 }
+    }
+
+    /// Checks the read bytes until the root element starts.
+    fn check(&mut self, data: &[u8]) -> io::Result<()> {
+        for byte in data {
+            self.markup = match (self.markup, *byte) {
+                (Markup::Root, _) => return Ok(()),
+                (Markup::Outside, b'<') => Markup::Open,
+                (Markup::Outside, _) => Markup::Outside,
+                (Markup::Open, b'?') => Markup::Instruction,
+                (Markup::Open, b'!') => Markup::Declaration,
+                (Markup::Instruction, b'?') | (Markup::InstructionEnd, b'?') => Markup::InstructionEnd,
+                (Markup::InstructionEnd, b'>') => Markup::Outside,
+                (Markup::Instruction, _) | (Markup::InstructionEnd, _) => Markup::Instruction,
+                (Markup::Declaration, b'-') => Markup::CommentStart,
+                (Markup::Declaration, _) => {
+                    let error = Error::XmlSyntax {
+                        line: self.line + 1,
+                        column: self.column + 1,
+                        message: DOCTYPE_NOT_SUPPORTED.to_string(),
+                    };
+                    return Err(io::Error::new(io::ErrorKind::InvalidData, error));
+                }
+                (Markup::CommentStart, b'-') => Markup::Comment,
+                (Markup::Comment, b'-') => Markup::CommentDash,
+                (Markup::CommentDash, b'-') => Markup::CommentEnd,
+                (Markup::CommentDash, _) => Markup::Comment,
+                (Markup::CommentEnd, b'>') => Markup::Outside,
+                (Markup::Comment, _) => Markup::Comment,
+                // Root element or malformed markup, reported by xml-rs.
+                _ => Markup::Root,
+            };
+            if *byte == b'\n' {
+                self.line += 1;
+                self.column = 0;
+            } else if *byte & 0xc0 != 0x80 {
+                self.column += 1;
+            }
+        }
+        return Ok(());
+    }
+}
+
+impl<R: BufRead> Read for Prolog<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        if self.markup != Markup::Root {
+            self.check(&buf[..read])?;
+        }
+        return Ok(read);
+    }
+}
+
+/// Reads events of the document.
+pub(crate) struct EventReader<R: BufRead> {
+    events: Events<Prolog<R>>,
+}
+
+impl<R: BufRead> EventReader<R> {
+    pub(crate) fn new(source: R) -> EventReader<R> {
+        EventReader {
+            events: reader::EventReader::new(Prolog::new(source)).into_iter(),
+        }
+    }
+
+    pub(crate) fn get_mut(&mut self) -> &mut R {
+        return &mut self.events.source_mut().inner;
+    }
+}
+
+impl<R: BufRead> Iterator for EventReader<R> {
+    type Item = Result<Event, Error>;
+    fn next(&mut self) -> Option<Result<Event, Error>> {
+        loop {
+            match self.events.next()? {
+                Ok(ReadEvent::StartElement { name, attributes, .. }) => {
+                    let attributes = attributes.len();
+                    return Some(Ok(Event::Start { name: name.local_name, namespace: name.namespace, attributes }));
+                }
+                Ok(ReadEvent::EndElement { .. }) => {
+                    return Some(Ok(Event::End));
+                }
+                Ok(ReadEvent::Characters(data)) | Ok(ReadEvent::CData(data)) => {
+                    return Some(Ok(Event::Text(data)));
+                }
+                Ok(_) => {}
+                Err(error) => {
+                    return Some(Err(read_error(error)));
+                }
+            }
+        }
+    }
+}
+
+/// Writes markup of the document.
+pub(crate) struct XmlWriter<W: Write> {
+    writer: EventWriter<W>,
+}
+
+impl<W: Write> XmlWriter<W> {
+    pub(crate) fn new(out: W, indent: Option<&str>, line_separator: &str) -> XmlWriter<W> {
+        let mut config = EmitterConfig::new()
+            .perform_indent(indent.is_some())
+            .line_separator(line_separator.to_string())
+            .write_document_declaration(false);
+        // Text is escaped by `escape_pcdata`, which escapes quotes as well.
+        config.perform_escaping = false;
+        let config = match indent {
+            Some(indent) => config.indent_string(indent.to_string()),
+            None => config,
+        };
+        XmlWriter {
+            writer: config.create_writer(out),
+        }
+    }
+
+    pub(crate) fn start_document(
+        &mut self,
+        version: XmlVersion,
+        encoding: &str,
+        standalone: Option<bool>,
+    ) -> Result<(), Error> {
+        let version = match version {
+            XmlVersion::Version10 => xml::common::XmlVersion::Version10,
+            XmlVersion::Version11 => xml::common::XmlVersion::Version11,
+        };
+        let event = WriteEvent::StartDocument { version, encoding: Some(encoding), standalone };
+        return self.writer.write(event).map_err(write_error);
+    }
+
+    pub(crate) fn processing_instruction(&mut self, name: &str, data: &str) -> Result<(), Error> {
+        return self.writer.write(WriteEvent::processing_instruction(name, Some(data))).map_err(write_error);
+    }
+
+    pub(crate) fn comment(&mut self, text: &str) -> Result<(), Error> {
+        return self.writer.write(WriteEvent::comment(text)).map_err(write_error);
+    }
+
+    pub(crate) fn start_element(&mut self, name: &str, namespace: Option<&str>) -> Result<(), Error> {
+        let event = match namespace {
+            Some(namespace) => WriteEvent::start_element(name).default_ns(namespace),
+            None => WriteEvent::start_element(name),
+        };
+        return self.writer.write(event).map_err(write_error);
+    }
+
+    pub(crate) fn characters(&mut self, text: &str) -> Result<(), Error> {
+        return self.writer.write(WriteEvent::characters(&escape_pcdata(text))).map_err(write_error);
+    }
+
+    pub(crate) fn end_element(&mut self, name: &str) -> Result<(), Error> {
+        return self.writer.write(WriteEvent::end_element().name(name)).map_err(write_error);
+    }
+
+    pub(crate) fn inner_ref(&self) -> &W {
+        return self.writer.inner_ref();
+    }
+
+    pub(crate) fn inner_mut(&mut self) -> &mut W {
+        return self.writer.inner_mut();
+    }
+
+    pub(crate) fn into_inner(self) -> W {
+        return self.writer.into_inner();
+    }
+}
@@ -0,0 +1,22 @@
+//! Helpers for generated documents.
+
+/// Returns FNV-1a hash of the content, stable across builds and platforms.
+pub(crate) fn hash(content: &[u8]) -> u64 {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for byte in content {
+        hash ^= u64::from(*byte);
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    }
+    return hash;
+}
+
+/// Compresses the content with gzip.
+#[cfg(feature = "gzip")]
+pub(crate) fn gzip(content: &[u8]) -> Result<Vec<u8>, crate::Error> {
+    use flate2::Compression;
+    use flate2::write::GzEncoder;
+    use std::io::Write;
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(content)?;
+    return Ok(encoder.finish()?);
+}
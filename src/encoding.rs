@@ -0,0 +1,207 @@
+//! Transcoding of read documents into UTF-8.
+//!
+//! Encoding is detected from the byte order mark, from the first bytes of
+//! UTF-16 documents without the mark and from the XML declaration.
+//! UTF-8 documents are passed through without copying. Other encodings are
+//! decoded and the declaration is rewritten to declare UTF-8.
+use std::io::{self, BufRead, Read};
+use encoding_rs::{Decoder, Encoding, UTF_8, UTF_16BE, UTF_16LE};
+use crate::Error;
+
+/// Maximum size of the XML declaration looked up for the encoding.
+const MAX_DECLARATION: usize = 1024;
+
+/// Returns true for UTF-8 encoding label.
+pub(crate) fn is_utf8_label(label: &str) -> bool {
+    return Encoding::for_label(label.as_bytes()) == Some(UTF_8);
+}
+
+/// Returns range of the encoding value in the XML declaration at the start of the data.
+fn declared_encoding(data: &[u8]) -> Option<(usize, usize)> {
+    if !data.starts_with(b"<?xml") {
+        return None;
+    }
+    let end = data.windows(2).position(|pair| pair == b"?>")?;
+    let declaration = &data[..end];
+    let mut pos = declaration.windows(8).position(|name| name == b"encoding")? + 8;
+    while declaration.get(pos)?.is_ascii_whitespace() {
+        pos += 1;
+    }
+    if declaration[pos] != b'=' {
+        return None;
+    }
+    pos += 1;
+    while declaration.get(pos)?.is_ascii_whitespace() {
+        pos += 1;
+    }
+    let quote = declaration[pos];
+    if quote != b'"' && quote != b'\'' {
+        return None;
+    }
+    let start = pos + 1;
+    let len = declaration[start..].iter().position(|byte| *byte == quote)?;
+    return Some((start, start + len));
+}
+
+/// Reader returning the document transcoded into UTF-8.
+pub(crate) struct DecodeReader<R: BufRead> {
+    inner: R,
+    started: bool,
+    // Decoder of the document, `None` for UTF-8 documents.
+    decoder: Option<Decoder>,
+    buf: Vec<u8>,
+    pos: usize,
+    eof: bool,
+}
+
+impl<R: BufRead> DecodeReader<R> {
+    pub(crate) fn new(inner: R) -> DecodeReader<R> {
+        DecodeReader {
+            inner,
+            started: false,
+            decoder: None,
+            buf: Vec::new(),
+            pos: 0,
+            eof: false,
+        }
+    }
+
+    #[cfg(feature = "quick-xml")]
+    pub(crate) fn get_ref(&self) -> &R {
+        return &self.inner;
+    }
+
+    pub(crate) fn get_mut(&mut self) -> &mut R {
+        return &mut self.inner;
+    }
+
+    /// Reads the beginning of the document and detects its encoding.
+    fn start(&mut self) -> io::Result<()> {
+        self.started = true;
+        let mut prefix = Vec::new();
+        while prefix.len() < MAX_DECLARATION && !prefix.contains(&b'>') {
+            let available = self.inner.fill_buf()?;
+            if available.is_empty() {
+                break;
+            }
+            let len = available.len().min(MAX_DECLARATION - prefix.len());
+            let len = available[..len].iter().position(|byte| *byte == b'>').map_or(len, |pos| pos + 1);
+            prefix.extend_from_slice(&available[..len]);
+            self.inner.consume(len);
+        }
+        let (encoding, bom) = if prefix.starts_with(b"\xEF\xBB\xBF") {
+            (UTF_8, 3)
+        } else if prefix.starts_with(b"\xFF\xFE") {
+            (UTF_16LE, 2)
+        } else if prefix.starts_with(b"\xFE\xFF") {
+            (UTF_16BE, 2)
+        } else if prefix.starts_with(b"<\0?\0") {
+            (UTF_16LE, 0)
+        } else if prefix.starts_with(b"\0<\0?") {
+            (UTF_16BE, 0)
+        } else {
+            let encoding = match declared_encoding(&prefix) {
+                Some((start, end)) => {
+                    let label = &prefix[start..end];
+                    match Encoding::for_label(label) {
+                        // The document is not UTF-16, its first bytes would tell otherwise.
+                        Some(encoding) if encoding == UTF_16LE || encoding == UTF_16BE => UTF_8,
+                        Some(encoding) => encoding,
+                        None => {
+                            let encoding = String::from_utf8_lossy(label).into_owned();
+                            return Err(io::Error::new(
+                                io::ErrorKind::InvalidData,
+                                Error::UnsupportedEncoding { encoding },
+                            ));
+                        }
+                    }
+                }
+                None => UTF_8,
+            };
+            (encoding, 0)
+        };
+        if encoding == UTF_8 {
+            prefix.drain(..bom);
+            // The declaration may name another encoding, e.g. UTF-16 of a document in UTF-8.
+            if let Some((start, end)) = declared_encoding(&prefix) {
+                if Encoding::for_label(&prefix[start..end]) != Some(UTF_8) {
+                    prefix.splice(start..end, b"UTF-8".iter().copied());
+                }
+            }
+            self.buf = prefix;
+            return Ok(());
+        }
+        let mut decoder = encoding.new_decoder_without_bom_handling();
+        decode(&mut decoder, &prefix[bom..], false, &mut self.buf);
+        self.decoder = Some(decoder);
+        // End of the declaration may be split between the prefix and the following bytes.
+        while self.buf.starts_with(b"<?xml") && !self.buf.windows(2).any(|pair| pair == b"?>")
+            && self.buf.len() < MAX_DECLARATION && !self.eof {
+            self.decode_next()?;
+        }
+        if let Some((start, end)) = declared_encoding(&self.buf) {
+            self.buf.splice(start..end, b"UTF-8".iter().copied());
+        }
+        return Ok(());
+    }
+
+    /// Decodes the next chunk of the inner stream, appending it to the buffer.
+    fn decode_next(&mut self) -> io::Result<()> {
+        let decoder = match self.decoder {
+            Some(ref mut decoder) => decoder,
+            None => return Ok(()),
+        };
+        let available = self.inner.fill_buf()?;
+        let len = available.len();
+        self.eof = len == 0;
+        decode(decoder, available, self.eof, &mut self.buf);
+        self.inner.consume(len);
+        return Ok(());
+    }
+}
+
+fn decode(decoder: &mut Decoder, src: &[u8], last: bool, dst: &mut Vec<u8>) {
+    let start = dst.len();
+    let len = decoder.max_utf8_buffer_length(src.len()).unwrap_or(src.len() * 3 + 16);
+    dst.resize(start + len, 0);
+    let (_, _, written, _) = decoder.decode_to_utf8(src, &mut dst[start..], last);
+    dst.truncate(start + written);
+}
+
+impl<R: BufRead> Read for DecodeReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let available = self.fill_buf()?;
+        let len = available.len().min(buf.len());
+        buf[..len].copy_from_slice(&available[..len]);
+        self.consume(len);
+        return Ok(len);
+    }
+}
+
+impl<R: BufRead> BufRead for DecodeReader<R> {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        if !self.started {
+            self.start()?;
+        }
+        if self.pos < self.buf.len() {
+            return Ok(&self.buf[self.pos..]);
+        }
+        if self.decoder.is_none() {
+            return self.inner.fill_buf();
+        }
+        self.buf.clear();
+        self.pos = 0;
+        while self.buf.is_empty() && !self.eof {
+            self.decode_next()?;
+        }
+        return Ok(&self.buf);
+    }
+
+    fn consume(&mut self, amt: usize) {
+        if self.pos < self.buf.len() {
+            self.pos = (self.pos + amt).min(self.buf.len());
+        } else if self.decoder.is_none() {
+            self.inner.consume(amt);
+        }
+    }
+}
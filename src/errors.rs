@@ -1,39 +1,201 @@
-use std::{fmt, error};
-use xml;
+use std::{fmt, error, io};
+use url::Url;
+use chrono_utils::parser::error::ParseError as DateTimeParseError;
+
+/// Sitemap protocol limit.
+#[derive(Debug,Clone,Copy,PartialEq,Eq)]
+pub enum Limit {
+    /// Maximum number of entries in a single file.
+    Entries(usize),
+    /// Maximum size of a single uncompressed file in bytes.
+    Bytes(usize),
+    /// Maximum nesting depth of elements in a read document.
+    Depth(usize),
+    /// Maximum length of a text in a read document in bytes.
+    FieldLength(usize),
+    /// Maximum number of attributes of an element in a read document.
+    Attributes(usize),
+}
+
+impl fmt::Display for Limit {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Limit::Entries(max) => write!(f, "{} entries", max),
+            Limit::Bytes(max) => write!(f, "{} bytes", max),
+            Limit::Depth(max) => write!(f, "{} nested elements", max),
+            Limit::FieldLength(max) => write!(f, "{} bytes of text", max),
+            Limit::Attributes(max) => write!(f, "{} attributes per element", max),
+        }
+    }
+}
+
 /// Sitemap errors
 #[derive(Debug)]
 pub enum Error {
-	/// Unable to write data into writer
-    XmlWriteError(xml::writer::Error),
-    /// Error
-    Invalid(String),
+    /// I/O error of the underlying stream.
+    Io(io::Error),
+    /// Malformed XML document.
+    XmlSyntax {
+        /// Line of the error, counting from 1.
+        line: u64,
+        /// Column of the error, counting from 1.
+        column: u64,
+        /// Error description.
+        message: String,
+    },
+    /// Location is not a valid url.
+    InvalidUrl {
+        /// Rejected value.
+        value: String,
+        /// Url parse error.
+        error: url::ParseError,
+    },
+    /// Modification time is not a valid W3C datetime.
+    InvalidLastMod {
+        /// Rejected value.
+        value: String,
+        /// Datetime parse error.
+        error: DateTimeParseError,
+    },
+    /// Priority is not a number between 0 and 1.
+    InvalidPriority {
+        /// Rejected value.
+        value: String,
+    },
+    /// Change frequency is not one of the protocol values.
+    InvalidChangeFreq {
+        /// Rejected value.
+        value: String,
+    },
+    /// Entry has no location.
+    MissingLocation,
+    /// Url is out of the sitemap scope.
+    ScopeViolation(Url),
+    /// Writing the entry would exceed the protocol limit
+    /// or the read document exceeds the reader limit.
+    LimitExceeded(Limit),
+    /// Ignore glob of the generator is malformed.
+    InvalidGlob {
+        /// Rejected glob.
+        pattern: String,
+        /// Glob parse error description.
+        message: String,
+    },
+    /// Character encoding of the document is not supported.
+    UnsupportedEncoding {
+        /// Name of the encoding.
+        encoding: String,
+    },
+    /// Sitemap extension is not supported.
+    /// Reported by validating readers for entry children in other namespaces.
+    UnsupportedExtension {
+        /// Namespace of the extension.
+        namespace: String,
+    },
+    /// Requested part of the paginated sitemap does not exist.
+    InvalidPart {
+        /// Requested part, counting from 1.
+        part: usize,
+        /// Number of parts.
+        parts: usize,
+    },
 }
 
-impl From<xml::writer::Error> for Error {
-    fn from(err: xml::writer::Error) -> Error {
-        Error::XmlWriteError(err)
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Error {
+        Error::Io(err)
+    }
+}
+
+pub(crate) fn clone_io_error(err: &io::Error) -> io::Error {
+    io::Error::new(err.kind(), err.to_string())
+}
+
+/// Converts I/O error of the read document, unwrapping sitemap errors of the transcoding.
+pub(crate) fn read_io_error(err: &io::Error) -> Error {
+    match err.get_ref().and_then(|inner| inner.downcast_ref::<Error>()) {
+        Some(inner) => inner.clone(),
+        None => Error::Io(clone_io_error(err)),
+    }
+}
+
+impl Clone for Error {
+    fn clone(&self) -> Error {
+        match self {
+            Error::Io(err) => Error::Io(clone_io_error(err)),
+            Error::XmlSyntax { line, column, message } => {
+                Error::XmlSyntax { line: *line, column: *column, message: message.clone() }
+            }
+            Error::InvalidUrl { value, error } => {
+                Error::InvalidUrl { value: value.clone(), error: *error }
+            }
+            Error::InvalidLastMod { value, error } => {
+                Error::InvalidLastMod { value: value.clone(), error: *error }
+            }
+            Error::InvalidPriority { value } => Error::InvalidPriority { value: value.clone() },
+            Error::InvalidChangeFreq { value } => Error::InvalidChangeFreq { value: value.clone() },
+            Error::MissingLocation => Error::MissingLocation,
+            Error::ScopeViolation(url) => Error::ScopeViolation(url.clone()),
+            Error::LimitExceeded(limit) => Error::LimitExceeded(*limit),
+            Error::InvalidGlob { pattern, message } => {
+                Error::InvalidGlob { pattern: pattern.clone(), message: message.clone() }
+            }
+            Error::UnsupportedEncoding { encoding } => {
+                Error::UnsupportedEncoding { encoding: encoding.clone() }
+            }
+            Error::UnsupportedExtension { namespace } => {
+                Error::UnsupportedExtension { namespace: namespace.clone() }
+            }
+            Error::InvalidPart { part, parts } => Error::InvalidPart { part: *part, parts: *parts },
+        }
     }
 }
 
 impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
-            Error::XmlWriteError(ref err) => write!(f, "sitemap error: {}", err),
-            Error::Invalid(s) => write!(f, "sitemap error: {}", s),
+            Error::Io(err) => write!(f, "sitemap error: {}", err),
+            Error::XmlSyntax { line, column, message } => {
+                write!(f, "sitemap error: {}:{}: {}", line, column, message)
+            }
+            Error::InvalidUrl { value, error } => {
+                write!(f, "sitemap error: invalid url '{}': {}", value, error)
+            }
+            Error::InvalidLastMod { value, error } => {
+                write!(f, "sitemap error: invalid lastmod '{}': {}", value, error)
+            }
+            Error::InvalidPriority { value } => {
+                write!(f, "sitemap error: priority '{}' should be between 0 and 1", value)
+            }
+            Error::InvalidChangeFreq { value } => {
+                write!(f, "sitemap error: invalid changefreq '{}'", value)
+            }
+            Error::MissingLocation => write!(f, "sitemap error: location is required"),
+            Error::ScopeViolation(url) => write!(f, "sitemap error: url {} is out of scope", url),
+            Error::LimitExceeded(limit) => write!(f, "sitemap error: limit of {} exceeded", limit),
+            Error::InvalidGlob { pattern, message } => {
+                write!(f, "sitemap error: invalid glob '{}': {}", pattern, message)
+            }
+            Error::UnsupportedEncoding { encoding } => {
+                write!(f, "sitemap error: encoding {} is not supported", encoding)
+            }
+            Error::UnsupportedExtension { namespace } => {
+                write!(f, "sitemap error: extension {} is not supported", namespace)
+            }
+            Error::InvalidPart { part, parts } => {
+                write!(f, "sitemap error: part {} is out of 1..={}", part, parts)
+            }
         }
     }
 }
 
 impl error::Error for Error {
-    fn cause(&self) -> Option<&dyn error::Error> {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
         match self {
-            Error::XmlWriteError(ref err) => Some(err),
+            Error::Io(err) => Some(err),
+            Error::InvalidUrl { error, .. } => Some(error),
+            Error::InvalidLastMod { error, .. } => Some(error),
             _ => None,
         }
     }
-    fn description(&self) -> &str {
-        // This method is soft-deprecated.
-        "sitemap error"
-    }
 }
-
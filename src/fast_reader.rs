@@ -0,0 +1,339 @@
+//! Contains fast sitemap reader. Requires `quick-xml` feature.
+//!
+//! `FastSiteMapReader` returns the same entities as
+//! [`SiteMapReader`](../reader/struct.SiteMapReader.html), but tracks its position
+//! in the document with a fixed state instead of a path of tag names,
+//! reuses its read buffer and moves complete entries out without cloning.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use sitemap::fast_reader::FastSiteMapReader;
+//! use sitemap::reader::SiteMapEntity;
+//! use std::fs::File;
+//! use std::io::BufReader;
+//! fn main() {
+//!     let file = File::open("tests/documents/sitemap1.xml").expect("Unable to open file.");
+//!     let parser = FastSiteMapReader::new(BufReader::new(file));
+//!     let urls = parser
+//!         .filter_map(|entity| match entity {
+//!             SiteMapEntity::Url(url_entry) => Some(url_entry),
+//!             _ => None,
+//!         })
+//!         .count();
+//!     println!("urls = {}", urls);
+//! }
+//! ```
+use crate::structs;
+use crate::Error;
+use crate::scope::SiteMapScope;
+use crate::reader::{SiteMapEntity, EntryChecker, ReaderLimits, LimitedRead};
+use crate::backend::DOCTYPE_NOT_SUPPORTED;
+use crate::backend::quick::{Position, count_attributes};
+use crate::encoding::DecodeReader;
+use std::io::BufRead;
+use std::mem;
+use quick_xml::events::Event;
+use quick_xml::NsReader;
+use quick_xml::name::ResolveResult;
+
+/// Field of an entry.
+#[derive(Clone,Copy,Debug,PartialEq,Eq)]
+enum Field {
+    Loc,
+    LastMod,
+    ChangeFreq,
+    Priority,
+}
+
+impl Field {
+    fn of_url(name: &[u8]) -> Option<Field> {
+        if name.eq_ignore_ascii_case(b"loc") {
+            return Some(Field::Loc);
+        } else if name.eq_ignore_ascii_case(b"lastmod") {
+            return Some(Field::LastMod);
+        } else if name.eq_ignore_ascii_case(b"changefreq") {
+            return Some(Field::ChangeFreq);
+        } else if name.eq_ignore_ascii_case(b"priority") {
+            return Some(Field::Priority);
+        }
+        return None;
+    }
+
+    fn of_sitemap(name: &[u8]) -> Option<Field> {
+        return Field::of_url(name).filter(|field| matches!(field, Field::Loc | Field::LastMod));
+    }
+}
+
+/// Position of the reader in the document.
+#[derive(Clone,Copy,Debug,PartialEq,Eq)]
+enum State {
+    Document,
+    UrlSet,
+    Url,
+    UrlField(Field),
+    SiteMapIndex,
+    SiteMap,
+    SiteMapField(Field),
+    End,
+}
+
+/// Collects entries of the document.
+struct Entities {
+    text: String,
+    state: State,
+    // Depth of elements unknown to the protocol, skipped with their content.
+    unknown: usize,
+    url_item: structs::UrlEntry,
+    sitemap_item: structs::SiteMapEntry,
+    entries: EntryChecker,
+}
+
+impl Entities {
+    fn open_tag(&mut self, name: &[u8], namespace: Option<&[u8]>) {
+        if self.unknown > 0 {
+            self.unknown += 1;
+            return;
+        }
+        if let (State::Url | State::SiteMap, Some(namespace)) = (self.state, namespace) {
+            self.entries.child_namespace(&String::from_utf8_lossy(namespace));
+        }
+        let next = match self.state {
+            // Like `SiteMapReader`, documents with several root elements are accepted.
+            State::Document | State::End if name.eq_ignore_ascii_case(b"urlset") => Some(State::UrlSet),
+            State::Document | State::End if name.eq_ignore_ascii_case(b"sitemapindex") => {
+                Some(State::SiteMapIndex)
+            }
+            State::UrlSet if name.eq_ignore_ascii_case(b"url") => Some(State::Url),
+            State::Url => Field::of_url(name).map(State::UrlField),
+            State::SiteMapIndex if name.eq_ignore_ascii_case(b"sitemap") => Some(State::SiteMap),
+            State::SiteMap => Field::of_sitemap(name).map(State::SiteMapField),
+            _ => None,
+        };
+        match next {
+            Some(State::Url) => {
+                self.url_item = structs::UrlEntry::new();
+                self.entries.start_entry();
+            }
+            Some(State::SiteMap) => {
+                self.sitemap_item = structs::SiteMapEntry::new();
+                self.entries.start_entry();
+            }
+            Some(State::UrlSet) | Some(State::SiteMapIndex) => {
+                self.entries.root_namespace(namespace.map(|namespace| String::from_utf8_lossy(namespace)).as_deref());
+            }
+            Some(State::UrlField(_)) | Some(State::SiteMapField(_)) => {
+                self.text.clear();
+            }
+            _ => {}
+        }
+        match next {
+            Some(next) => self.state = next,
+            None => self.unknown = 1,
+        }
+    }
+
+    fn close_tag(&mut self) -> Option<SiteMapEntity> {
+        if self.unknown > 0 {
+            self.unknown -= 1;
+            if self.unknown == 0 && matches!(self.state, State::Document | State::End) {
+                self.state = State::End;
+            }
+            return None;
+        }
+        match self.state {
+            State::UrlSet | State::SiteMapIndex => {
+                self.state = State::End;
+            }
+            State::Url => {
+                self.state = State::UrlSet;
+                let entry = mem::take(&mut self.url_item);
+                return Some(self.entries.url(entry));
+            }
+            State::SiteMap => {
+                self.state = State::SiteMapIndex;
+                let entry = mem::take(&mut self.sitemap_item);
+                return Some(self.entries.sitemap(entry));
+            }
+            State::UrlField(field) => {
+                self.state = State::Url;
+                if let Some(data) = self.field_text() {
+                    match field {
+                        Field::Loc => self.url_item.loc = self.entries.location(data),
+                        Field::LastMod => self.url_item.lastmod = self.entries.lastmod(data),
+                        Field::ChangeFreq => self.url_item.changefreq = self.entries.changefreq(data),
+                        Field::Priority => self.url_item.priority = self.entries.priority(data),
+                    }
+                }
+            }
+            State::SiteMapField(field) => {
+                self.state = State::SiteMap;
+                if let Some(data) = self.field_text() {
+                    match field {
+                        Field::Loc => self.sitemap_item.loc = self.entries.location(data),
+                        _ => self.sitemap_item.lastmod = self.entries.lastmod(data),
+                    }
+                }
+            }
+            State::Document | State::End => {}
+        }
+        return None;
+    }
+
+    /// Takes text of the closed field, whitespace only text is ignored.
+    fn field_text(&mut self) -> Option<String> {
+        if self.text.trim().is_empty() {
+            return None;
+        }
+        return Some(mem::take(&mut self.text));
+    }
+
+    fn in_field(&self) -> bool {
+        return self.unknown == 0 && matches!(self.state, State::UrlField(_) | State::SiteMapField(_));
+    }
+}
+
+/// A fast reader of sitemaps from `std::io::BufRead` instance.
+///
+/// It's provides iterator over `SiteMapEntity`.
+pub struct FastSiteMapReader<R: BufRead> {
+    parser: NsReader<Position<DecodeReader<LimitedRead<R>>>>,
+    buf: Vec<u8>,
+    entities: Entities,
+    limits: ReaderLimits,
+    depth: usize,
+    entries_read: usize,
+    failed: bool,
+}
+
+impl<R: BufRead> FastSiteMapReader<R> {
+    /// Creates a new reader, consuming the given stream.
+    pub fn new(source: R) -> FastSiteMapReader<R> {
+        let source = DecodeReader::new(LimitedRead::new(source));
+        let mut parser = NsReader::from_reader(Position::new(source));
+        parser.config_mut().expand_empty_elements = true;
+        FastSiteMapReader {
+            parser,
+            buf: Vec::new(),
+            entities: Entities {
+                text: String::new(),
+                state: State::Document,
+                unknown: 0,
+                url_item: structs::UrlEntry::new(),
+                sitemap_item: structs::SiteMapEntry::new(),
+                entries: EntryChecker::default(),
+            },
+            limits: ReaderLimits::default(),
+            depth: 0,
+            entries_read: 0,
+            failed: false,
+        }
+    }
+
+    /// Checks urls against the scope of the sitemap.
+    /// Urls out of scope are returned as `SiteMapEntity::OutOfScope`.
+    pub fn with_scope(mut self, scope: SiteMapScope) -> FastSiteMapReader<R> {
+        self.entities.entries.set_scope(scope);
+        return self;
+    }
+
+    /// Validates entries.
+    /// Entries with invalid or missing fields are returned as `SiteMapEntity::InvalidUrl`
+    /// and `SiteMapEntity::InvalidSiteMap` listing every problem of the entry.
+    /// Urls out of scope are reported as `Error::ScopeViolation` problem,
+    /// children in other namespaces than the sitemap one as `Error::UnsupportedExtension`.
+    pub fn with_validation(mut self) -> FastSiteMapReader<R> {
+        self.entities.entries.set_validation();
+        return self;
+    }
+
+    /// Replaces default limits of the read document.
+    pub fn with_limits(mut self, limits: ReaderLimits) -> FastSiteMapReader<R> {
+        self.parser.get_mut().inner.get_mut().set_limits(&limits);
+        self.limits = limits;
+        return self;
+    }
+
+    /// Returns reference to the underlying stream.
+    pub fn get_ref(&self) -> &R {
+        return self.parser.get_ref().inner.get_ref().get_ref();
+    }
+
+    fn next_entity(&mut self) -> Result<Option<SiteMapEntity>, Error> {
+        loop {
+            self.buf.clear();
+            match self.parser.read_event_into(&mut self.buf) {
+                Ok(Event::Start(element)) => {
+                    self.depth += 1;
+                    self.limits.check_depth(self.depth)?;
+                    self.limits.check_attributes(count_attributes(&element))?;
+                    let (resolved, name) = self.parser.resolve_element(element.name());
+                    let namespace = match resolved {
+                        ResolveResult::Bound(namespace) => Some(namespace.into_inner()),
+                        _ => None,
+                    };
+                    self.entities.open_tag(name.as_ref(), namespace);
+                }
+                Ok(Event::End(_)) => {
+                    self.depth -= 1;
+                    if let Some(entity) = self.entities.close_tag() {
+                        self.entries_read += 1;
+                        self.limits.check_entries(self.entries_read)?;
+                        return Ok(Some(entity));
+                    }
+                }
+                Ok(Event::Text(text)) => {
+                    self.limits.check_field_length(text.len())?;
+                    if self.entities.in_field() {
+                        match text.unescape() {
+                            Ok(text) => self.entities.text.push_str(&text),
+                            Err(error) => return Err(self.parser.get_ref().read_error(error)),
+                        }
+                        self.limits.check_field_length(self.entities.text.len())?;
+                    }
+                }
+                Ok(Event::CData(data)) => {
+                    self.limits.check_field_length(data.len())?;
+                    if self.entities.in_field() {
+                        match data.decode() {
+                            Ok(data) => self.entities.text.push_str(&data),
+                            Err(error) => return Err(self.parser.get_ref().read_error(error.into())),
+                        }
+                        self.limits.check_field_length(self.entities.text.len())?;
+                    }
+                }
+                Ok(Event::DocType(_)) => {
+                    return Err(self.parser.get_ref().syntax_error(DOCTYPE_NOT_SUPPORTED));
+                }
+                Ok(Event::Eof) => {
+                    if self.entities.state != State::End {
+                        return Err(self.parser.get_ref().syntax_error("unexpected end of document"));
+                    }
+                    return Ok(None);
+                }
+                Ok(_) => {}
+                Err(error) => {
+                    return Err(self.parser.get_ref().read_error(error));
+                }
+            }
+        }
+    }
+}
+
+impl<R: BufRead> Iterator for FastSiteMapReader<R> {
+    type Item = SiteMapEntity;
+    fn next(&mut self) -> Option<SiteMapEntity> {
+        if self.failed {
+            return None;
+        }
+        match self.next_entity() {
+            Ok(entity) => {
+                return entity;
+            }
+            Err(error) => {
+                self.failed = true;
+                return Some(SiteMapEntity::Err(error));
+            }
+        }
+    }
+}
@@ -0,0 +1,303 @@
+//! Contains sitemap generation from a static site directory. Requires `generator` feature.
+//!
+//! Every `.html` and `.htm` file of the directory is listed: `index.html`
+//! becomes the url of its directory with a trailing slash, other files are
+//! mapped according to `UrlStyle`. File modification times are used as `lastmod`
+//! unless another [`LastModProvider`](../lastmod/trait.LastModProvider.html) is given.
+//! Files are written while the directory is walked, `write_sequence_from_dir`
+//! splits sites exceeding the protocol limits into several documents.
+//!
+//! # Examples
+//!
+//! ```rust,no_run
+//! use sitemap::generator::{GeneratorOptions, UrlStyle, write_from_dir};
+//! use sitemap::writer::SiteMapWriter;
+//! use url::Url;
+//! use std::fs::File;
+//! fn main() {
+//!     let options = GeneratorOptions::new()
+//!         .url_style(UrlStyle::StripExtension)
+//!         .ignore("drafts/**")
+//!         .ignore("**/404.html");
+//!     let base_url = Url::parse("https://www.example.com/").unwrap();
+//!     let output = File::create("public/sitemap.xml").expect("Unable to create file.");
+//!     let sitemap_writer = SiteMapWriter::new(output);
+//!     let mut urlwriter = sitemap_writer.start_urlset().expect("Unable to write urlset");
+//!     write_from_dir("public", &base_url, &options, &mut urlwriter).expect("Unable to generate sitemap");
+//!     urlwriter.end().expect("Unable to write close tags");
+//! }
+//! ```
+use crate::{Error, Limit};
+use crate::structs::{UrlEntry, Location};
+use crate::writer::{UrlSetWriter, SiteMapSequence};
+use crate::lastmod::{LastModProvider, FileTime};
+use glob::{Pattern, MatchOptions};
+use std::fmt;
+use std::fs;
+use std::io;
+use std::sync::Arc;
+use std::io::Write;
+use std::path::Path;
+use url::Url;
+
+/// How html files other than `index.html` are mapped to urls.
+#[derive(Clone,Copy,Debug,PartialEq,Eq)]
+pub enum UrlStyle {
+    /// `about.html` is listed as `about.html`.
+    File,
+    /// `about.html` is listed as `about`.
+    StripExtension,
+    /// `about.html` is listed as `about/`.
+    TrailingSlash,
+}
+
+/// Options of sitemap generation.
+#[derive(Clone)]
+pub struct GeneratorOptions {
+    ignore: Vec<String>,
+    url_style: UrlStyle,
+    lastmod: Option<Arc<dyn LastModProvider + Send + Sync>>,
+}
+
+impl GeneratorOptions {
+    /// Creates options listing every html file with its modification time.
+    pub fn new() -> GeneratorOptions {
+        GeneratorOptions {
+            ignore: Vec::new(),
+            url_style: UrlStyle::File,
+            lastmod: Some(Arc::new(FileTime)),
+        }
+    }
+
+    /// Skips files and directories matching the glob.
+    ///
+    /// Globs are matched against paths relative to the root with `/` separators.
+    /// `*` does not match `/`, `**` matches any number of directories.
+    pub fn ignore<S: Into<String>>(mut self, glob: S) -> GeneratorOptions {
+        self.ignore.push(glob.into());
+        return self;
+    }
+
+    /// Defines how html files are mapped to urls. Defaults to `UrlStyle::File`.
+    pub fn url_style(mut self, url_style: UrlStyle) -> GeneratorOptions {
+        self.url_style = url_style;
+        return self;
+    }
+
+    /// Enables or disables `lastmod` taken from file modification times.
+    pub fn lastmod(mut self, lastmod: bool) -> GeneratorOptions {
+        self.lastmod = if lastmod { Some(Arc::new(FileTime)) } else { None };
+        return self;
+    }
+
+    /// Takes `lastmod` from the provider, e.g. `GitHistory`.
+    pub fn lastmod_provider<P: LastModProvider + Send + Sync + 'static>(mut self, provider: P) -> GeneratorOptions {
+        self.lastmod = Some(Arc::new(provider));
+        return self;
+    }
+
+    fn patterns(&self) -> Result<Vec<Pattern>, Error> {
+        let mut patterns = Vec::new();
+        for glob in &self.ignore {
+            match Pattern::new(glob) {
+                Ok(pattern) => patterns.push(pattern),
+                Err(error) => {
+                    return Err(Error::InvalidGlob { pattern: glob.clone(), message: error.to_string() });
+                }
+            }
+        }
+        return Ok(patterns);
+    }
+}
+
+impl fmt::Debug for GeneratorOptions {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("GeneratorOptions")
+            .field("ignore", &self.ignore)
+            .field("url_style", &self.url_style)
+            .field("lastmod", &self.lastmod.is_some())
+            .finish()
+    }
+}
+
+impl Default for GeneratorOptions {
+    fn default() -> GeneratorOptions {
+        return GeneratorOptions::new();
+    }
+}
+
+struct Walker<'a, F: FnMut(UrlEntry) -> Result<(), Error>> {
+    base_url: &'a Url,
+    options: &'a GeneratorOptions,
+    patterns: Vec<Pattern>,
+    visit: F,
+}
+
+impl<'a, F: FnMut(UrlEntry) -> Result<(), Error>> Walker<'a, F> {
+    fn is_ignored(&self, relative: &str) -> bool {
+        let options = MatchOptions {
+            case_sensitive: true,
+            require_literal_separator: true,
+            require_literal_leading_dot: false,
+        };
+        return self.patterns.iter().any(|pattern| pattern.matches_with(relative, options));
+    }
+
+    fn walk(&mut self, directory: &Path, segments: &mut Vec<String>) -> Result<(), Error> {
+        let mut children = Vec::new();
+        for child in fs::read_dir(directory)? {
+            children.push(child?);
+        }
+        children.sort_by_key(|child| child.file_name());
+        for child in children {
+            let name = match child.file_name().into_string() {
+                Ok(name) => name,
+                // Urls of files with non unicode names are ambiguous.
+                Err(_) => continue,
+            };
+            let path = child.path();
+            let mut relative = segments.join("/");
+            if !relative.is_empty() {
+                relative.push('/');
+            }
+            relative.push_str(&name);
+            if self.is_ignored(&relative) {
+                continue;
+            }
+            let file_type = child.file_type()?;
+            if file_type.is_dir() {
+                segments.push(name);
+                self.walk(&path, segments)?;
+                segments.pop();
+                continue;
+            }
+            // Symlinked directories are not followed to avoid cycles.
+            let metadata = match fs::metadata(&path) {
+                Ok(metadata) => metadata,
+                // Dangling symlink.
+                Err(ref error) if error.kind() == io::ErrorKind::NotFound => continue,
+                Err(error) => return Err(Error::Io(error)),
+            };
+            if !metadata.is_file() {
+                continue;
+            }
+            let stem = match html_stem(&name) {
+                Some(stem) => stem,
+                None => continue,
+            };
+            let mut url_segments: Vec<&str> = segments.iter().map(String::as_str).collect();
+            match (stem, self.options.url_style) {
+                ("index", _) => url_segments.push(""),
+                (_, UrlStyle::File) => url_segments.push(&name),
+                (stem, UrlStyle::StripExtension) => url_segments.push(stem),
+                (stem, UrlStyle::TrailingSlash) => url_segments.extend([stem, ""]),
+            }
+            let mut entry = UrlEntry::new();
+            entry.loc = Location::Url(self.url(&url_segments)?);
+            if let Some(ref provider) = self.options.lastmod {
+                provider.fill(&mut entry, &path)?;
+            }
+            (self.visit)(entry)?;
+        }
+        return Ok(());
+    }
+
+    fn url(&self, segments: &[&str]) -> Result<Url, Error> {
+        let mut url = self.base_url.clone();
+        match url.path_segments_mut() {
+            Ok(mut path) => {
+                path.pop_if_empty().extend(segments);
+            }
+            Err(()) => {
+                return Err(Error::InvalidUrl {
+                    value: self.base_url.to_string(),
+                    error: url::ParseError::RelativeUrlWithCannotBeABaseBase,
+                });
+            }
+        }
+        return Ok(url);
+    }
+}
+
+fn html_stem(name: &str) -> Option<&str> {
+    let index = name.rfind('.')?;
+    let extension = &name[index + 1..];
+    if index == 0 || !(extension.eq_ignore_ascii_case("html") || extension.eq_ignore_ascii_case("htm")) {
+        return None;
+    }
+    return Some(&name[..index]);
+}
+
+/// Passes url entries of html files of `root` to `visit`, ordered by path.
+fn walk_dir<F: FnMut(UrlEntry) -> Result<(), Error>>(root: &Path, base_url: &Url, options: &GeneratorOptions, visit: F) -> Result<(), Error> {
+    let mut walker = Walker {
+        base_url,
+        options,
+        patterns: options.patterns()?,
+        visit,
+    };
+    return walker.walk(root, &mut Vec::new());
+}
+
+/// Lists html files of `root` as url entries relative to `base_url`, ordered by path.
+///
+/// `base_url` is treated as a directory, whether or not it ends with a slash.
+pub fn generate_from_dir<P: AsRef<Path>>(root: P, base_url: &Url, options: &GeneratorOptions) -> Result<Vec<UrlEntry>, Error> {
+    let mut entries = Vec::new();
+    walk_dir(root.as_ref(), base_url, options, |entry| {
+        entries.push(entry);
+        return Ok(());
+    })?;
+    return Ok(entries);
+}
+
+/// Writes html files of `root` into `urlset`, returns number of written urls.
+pub fn write_from_dir<P: AsRef<Path>, T: Write + Sized>(root: P, base_url: &Url, options: &GeneratorOptions, urlset: &mut UrlSetWriter<T>) -> Result<usize, Error> {
+    let mut count = 0;
+    walk_dir(root.as_ref(), base_url, options, |entry| {
+        urlset.url(entry)?;
+        count += 1;
+        return Ok(());
+    })?;
+    return Ok(count);
+}
+
+/// Writes html files of `root` into urlsets of the sequence.
+///
+/// The next document is started when the current one is full. Returns streams
+/// of the written documents, none if the directory has no html files.
+pub fn write_sequence_from_dir<P, T, F>(
+    root: P,
+    base_url: &Url,
+    options: &GeneratorOptions,
+    sequence: &mut SiteMapSequence<T, F>,
+) -> Result<Vec<T>, Error>
+where
+    P: AsRef<Path>,
+    T: Write + Sized,
+    F: FnMut(usize) -> Result<T, Error>,
+{
+    let mut documents = Vec::new();
+    let mut current: Option<UrlSetWriter<T>> = None;
+    walk_dir(root.as_ref(), base_url, options, |entry| {
+        if let Some(ref mut urlset) = current {
+            if urlset.remaining_urls() > 0 {
+                match urlset.url(entry.clone()) {
+                    Err(Error::LimitExceeded(Limit::Bytes(_))) => {}
+                    result => return result,
+                }
+            }
+            if let Some(urlset) = current.take() {
+                documents.push(urlset.finish()?);
+            }
+        }
+        let mut urlset = sequence.next_document()?.start_urlset()?;
+        urlset.url(entry)?;
+        current = Some(urlset);
+        return Ok(());
+    })?;
+    if let Some(urlset) = current {
+        documents.push(urlset.finish()?);
+    }
+    return Ok(documents);
+}
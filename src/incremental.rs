@@ -0,0 +1,194 @@
+//! Contains incremental regeneration of sitemap files. Requires `incremental` feature.
+//!
+//! Hashes of the parts are kept in a manifest file between runs. Parts whose
+//! content did not change are not rewritten and keep their `lastmod` in the
+//! sitemap index, so caches of unchanged files stay valid.
+//! Files are replaced atomically, the index after every part it references.
+//!
+//! # Examples
+//!
+//! ```rust,no_run
+//! use sitemap::incremental::IncrementalWriter;
+//! use sitemap::provider::{MemoryProvider, SiteMapPaginator};
+//! use sitemap::structs::UrlEntry;
+//! use chrono::Utc;
+//! use url::Url;
+//! fn main() {
+//!     let provider = MemoryProvider::new(vec![UrlEntry::from("https://www.example.com/")]);
+//!     let paginator = SiteMapPaginator::new(provider, Url::parse("https://www.example.com/").unwrap());
+//!     let regeneration = IncrementalWriter::new("public")
+//!         .write(&paginator, Utc::now().fixed_offset())
+//!         .expect("Unable to write sitemaps");
+//!     println!("rewritten parts = {:?}", regeneration.written);
+//! }
+//! ```
+use crate::Error;
+use crate::content;
+use crate::provider::{UrlProvider, SiteMapPaginator};
+use crate::structs::{SiteMapEntry, LastMod};
+use crate::writer::SiteMapWriter;
+use crate::output::{AtomicFile, file_name};
+use chrono::{DateTime, FixedOffset};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+const INDEX_NAME: &str = "sitemap.xml";
+const MANIFEST_HEADER: &str = "# sitemap manifest 1";
+
+/// Report of incremental regeneration.
+#[derive(Clone,Debug,Default,PartialEq,Eq)]
+pub struct Regeneration {
+    /// Parts which were written, counting from 1.
+    pub written: Vec<usize>,
+    /// Parts which did not change.
+    pub unchanged: Vec<usize>,
+    /// Files of parts which no longer exist and were removed.
+    pub removed: Vec<String>,
+    /// Whether the sitemap index was written.
+    pub index_written: bool,
+}
+
+#[derive(Clone,Debug,PartialEq)]
+struct ManifestEntry {
+    hash: u64,
+    lastmod: DateTime<FixedOffset>,
+}
+
+/// Writes the sitemap index and parts into a directory, skipping unchanged parts.
+#[derive(Clone,Debug)]
+pub struct IncrementalWriter {
+    directory: PathBuf,
+    manifest: PathBuf,
+}
+
+impl IncrementalWriter {
+    /// Creates writer into `directory` keeping manifest at `sitemap.manifest` in it.
+    /// The index is written as `sitemap.xml`, parts are named by the paginator.
+    pub fn new<P: AsRef<Path>>(directory: P) -> IncrementalWriter {
+        let directory = directory.as_ref().to_path_buf();
+        IncrementalWriter {
+            manifest: directory.join("sitemap.manifest"),
+            directory,
+        }
+    }
+
+    /// Defines path of the manifest file.
+    pub fn manifest<P: AsRef<Path>>(mut self, manifest: P) -> IncrementalWriter {
+        self.manifest = manifest.as_ref().to_path_buf();
+        return self;
+    }
+
+    /// Regenerates sitemaps of the paginator.
+    /// Rewritten parts get `now` as their `lastmod` in the index.
+    pub fn write<P: UrlProvider>(&self, paginator: &SiteMapPaginator<P>, now: DateTime<FixedOffset>) -> Result<Regeneration, Error> {
+        let previous = self.read_manifest()?;
+        let mut manifest = BTreeMap::new();
+        let mut regeneration = Regeneration::default();
+        let mut index = Vec::new();
+        let mut sitemap_index_writer = SiteMapWriter::new(&mut index).start_sitemapindex()?;
+        for part in 1..=paginator.parts()? {
+            let location = paginator.part_url(part)?;
+            let name = file_name(&location)?;
+            let mut body = Vec::new();
+            paginator.write_part(part, SiteMapWriter::new(&mut body))?;
+            let hash = content::hash(&body);
+            let lastmod = match previous.get(&name) {
+                Some(entry) if entry.hash == hash && self.directory.join(&name).is_file() => {
+                    regeneration.unchanged.push(part);
+                    entry.lastmod
+                }
+                _ => {
+                    self.write_file(&name, &body, paginator.is_gzip())?;
+                    regeneration.written.push(part);
+                    now
+                }
+            };
+            let mut entry = SiteMapEntry::from(location);
+            entry.lastmod = LastMod::DateTime(lastmod);
+            sitemap_index_writer.sitemap(entry)?;
+            manifest.insert(name, ManifestEntry { hash, lastmod });
+        }
+        sitemap_index_writer.end()?;
+        let hash = content::hash(&index);
+        let lastmod = match previous.get(INDEX_NAME) {
+            Some(entry) if entry.hash == hash && self.directory.join(INDEX_NAME).is_file() => entry.lastmod,
+            _ => {
+                self.write_file(INDEX_NAME, &index, false)?;
+                regeneration.index_written = true;
+                now
+            }
+        };
+        manifest.insert(INDEX_NAME.to_string(), ManifestEntry { hash, lastmod });
+        for name in previous.keys() {
+            if !manifest.contains_key(name) {
+                if let Err(error) = fs::remove_file(self.directory.join(name)) {
+                    if error.kind() != io::ErrorKind::NotFound {
+                        return Err(Error::Io(error));
+                    }
+                }
+                regeneration.removed.push(name.clone());
+            }
+        }
+        self.write_manifest(&manifest)?;
+        return Ok(regeneration);
+    }
+
+    fn write_file(&self, name: &str, body: &[u8], gzip: bool) -> Result<(), Error> {
+        let path = self.directory.join(name);
+        // Parts can be gzipped only with `gzip` feature.
+        if gzip {
+            #[cfg(feature = "gzip")]
+            {
+                return AtomicFile::publish(path, &content::gzip(body)?);
+            }
+        }
+        return AtomicFile::publish(path, body);
+    }
+
+    fn read_manifest(&self) -> Result<BTreeMap<String, ManifestEntry>, Error> {
+        let text = match fs::read_to_string(&self.manifest) {
+            Ok(text) => text,
+            Err(ref error) if error.kind() == io::ErrorKind::NotFound => {
+                return Ok(BTreeMap::new());
+            }
+            Err(error) => {
+                return Err(Error::Io(error));
+            }
+        };
+        let mut manifest = BTreeMap::new();
+        for line in text.lines() {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let mut fields = line.split(' ');
+            let entry = match (fields.next(), fields.next(), fields.next(), fields.next()) {
+                (Some(name), Some(hash), Some(lastmod), None) => {
+                    let hash = u64::from_str_radix(hash, 16).ok();
+                    let lastmod = DateTime::parse_from_rfc3339(lastmod).ok();
+                    hash.zip(lastmod).map(|(hash, lastmod)| (name, ManifestEntry { hash, lastmod }))
+                }
+                _ => None,
+            };
+            match entry {
+                Some((name, entry)) => {
+                    manifest.insert(name.to_string(), entry);
+                }
+                None => {
+                    let message = format!("malformed manifest line '{}'", line);
+                    return Err(Error::Io(io::Error::new(io::ErrorKind::InvalidData, message)));
+                }
+            }
+        }
+        return Ok(manifest);
+    }
+
+    fn write_manifest(&self, manifest: &BTreeMap<String, ManifestEntry>) -> Result<(), Error> {
+        let mut text = format!("{}\n", MANIFEST_HEADER);
+        for (name, entry) in manifest {
+            text.push_str(&format!("{} {:016x} {}\n", name, entry.hash, entry.lastmod.to_rfc3339()));
+        }
+        return AtomicFile::publish(&self.manifest, text.as_bytes());
+    }
+}
@@ -0,0 +1,143 @@
+//! Contains providers of modification times.
+//!
+//! File modification times are meaningless after a fresh checkout, so
+//! `GitHistory` takes the time of the last commit of every file instead.
+//! Generated files, which are usually not committed, are looked up as their
+//! sources with `GitHistory::source_root`.
+//!
+//! # Examples
+//!
+//! ```rust,no_run
+//! use sitemap::lastmod::{GitHistory, LastModProvider};
+//! use sitemap::structs::UrlEntry;
+//! use std::path::Path;
+//! fn main() {
+//!     let history = GitHistory::open(".").expect("Unable to read git history.");
+//!     let mut entry = UrlEntry::from("https://www.example.com/guide.html");
+//!     history.fill(&mut entry, Path::new("docs/guide.html")).expect("Unable to get lastmod.");
+//!     println!("lastmod = {:?}", entry.lastmod);
+//! }
+//! ```
+use crate::Error;
+use crate::structs::{UrlEntry, LastMod};
+use chrono::{DateTime, FixedOffset, Utc};
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Source of modification times of files.
+pub trait LastModProvider {
+    /// Returns modification time of the file, `LastMod::None` when unknown.
+    fn lastmod(&self, path: &Path) -> Result<LastMod, Error>;
+
+    /// Sets `lastmod` of the entry to modification time of the file.
+    fn fill(&self, entry: &mut UrlEntry, path: &Path) -> Result<(), Error> {
+        entry.lastmod = self.lastmod(path)?;
+        return Ok(());
+    }
+}
+
+/// Takes modification times from the file system.
+#[derive(Clone,Copy,Debug,Default)]
+pub struct FileTime;
+
+impl LastModProvider for FileTime {
+    fn lastmod(&self, path: &Path) -> Result<LastMod, Error> {
+        let metadata = fs::metadata(path)?;
+        match metadata.modified() {
+            Ok(modified) => {
+                return Ok(LastMod::DateTime(DateTime::<Utc>::from(modified).fixed_offset()));
+            }
+            // Modification times are not supported by the platform.
+            Err(_) => {
+                return Ok(LastMod::None);
+            }
+        }
+    }
+}
+
+/// Takes modification times from the last commits of files in a git repository.
+///
+/// History is read once by the local `git` executable. Files without commits
+/// have no modification time.
+#[derive(Clone,Debug)]
+pub struct GitHistory {
+    toplevel: PathBuf,
+    times: HashMap<PathBuf, DateTime<FixedOffset>>,
+    source_root: Option<(PathBuf, PathBuf)>,
+}
+
+impl GitHistory {
+    /// Reads history of the repository containing `repository` path.
+    pub fn open<P: AsRef<Path>>(repository: P) -> Result<GitHistory, Error> {
+        let repository = repository.as_ref();
+        let toplevel = git(repository, &["rev-parse", "--show-toplevel"])?;
+        let toplevel = fs::canonicalize(toplevel.trim_end_matches('\n'))?;
+        let log = git(&toplevel, &["log", "--format=%x01%cI", "--name-only", "-z"])?;
+        let mut times = HashMap::new();
+        let mut time = None;
+        for token in log.split('\0') {
+            let token = token.trim_start_matches('\n');
+            if let Some(date) = token.strip_prefix('\u{1}') {
+                time = DateTime::parse_from_rfc3339(date).ok();
+                continue;
+            }
+            if let (Some(time), false) = (time, token.is_empty()) {
+                times.entry(PathBuf::from(token))
+                    .and_modify(|last: &mut DateTime<FixedOffset>| *last = (*last).max(time))
+                    .or_insert(time);
+            }
+        }
+        return Ok(GitHistory { toplevel, times, source_root: None });
+    }
+
+    /// Looks up files under `output` as the files at the same relative path under `source`,
+    /// e.g. generated `public/docs/guide.html` as committed `site/docs/guide.html`.
+    pub fn source_root<O: Into<PathBuf>, S: Into<PathBuf>>(mut self, output: O, source: S) -> GitHistory {
+        self.source_root = Some((output.into(), source.into()));
+        return self;
+    }
+
+    /// Returns root directory of the repository.
+    pub fn toplevel(&self) -> &Path {
+        return &self.toplevel;
+    }
+}
+
+impl LastModProvider for GitHistory {
+    fn lastmod(&self, path: &Path) -> Result<LastMod, Error> {
+        let mut path = match fs::canonicalize(path) {
+            Ok(path) => path,
+            Err(_) => {
+                return Ok(LastMod::None);
+            }
+        };
+        if let Some((ref output, ref source)) = self.source_root {
+            if let (Ok(output), Ok(source)) = (fs::canonicalize(output), fs::canonicalize(source)) {
+                if let Ok(relative) = path.strip_prefix(&output) {
+                    path = source.join(relative);
+                }
+            }
+        }
+        let time = path.strip_prefix(&self.toplevel).ok().and_then(|path| self.times.get(path));
+        match time {
+            Some(time) => {
+                return Ok(LastMod::DateTime(*time));
+            }
+            None => {
+                return Ok(LastMod::None);
+            }
+        }
+    }
+}
+
+fn git(directory: &Path, args: &[&str]) -> Result<String, Error> {
+    let output = Command::new("git").arg("-C").arg(directory).args(args).output()?;
+    if !output.status.success() {
+        let message = format!("git {} failed: {}", args[0], String::from_utf8_lossy(&output.stderr).trim());
+        return Err(Error::Io(io::Error::other(message)));
+    }
+    return Ok(String::from_utf8_lossy(&output.stdout).into_owned());
+}
@@ -3,8 +3,27 @@
 //!
 //! [Reading sitemap](reader/index.html#examples).
 //! [Writing sitemap](writer/index.html#examples).
+#![allow(clippy::needless_return)]
 pub mod structs;
 pub mod reader;
+#[cfg(feature = "quick-xml")]
+pub mod fast_reader;
 pub mod writer;
+pub mod scope;
+pub mod schedule;
+#[cfg(feature = "generator")]
+pub mod generator;
+pub mod lastmod;
+pub mod provider;
+#[cfg(feature = "incremental")]
+pub mod incremental;
+#[cfg(feature = "output")]
+pub mod output;
+#[cfg(feature = "axum")]
+pub mod serve;
 mod errors;
-pub use errors::Error;
\ No newline at end of file
+mod backend;
+mod encoding;
+#[cfg(any(feature = "incremental", feature = "axum"))]
+mod content;
+pub use errors::{Error, Limit};
@@ -0,0 +1,155 @@
+//! Contains atomic file output. Requires `output` feature.
+//!
+//! `AtomicFile` writes into a temporary file placed next to the destination and
+//! replaces the destination only on commit, so readers never see a truncated
+//! document. The temporary file is removed when the output is dropped uncommitted.
+//! Commit parts before the sitemap index, so the index never references missing parts,
+//! `publish_paginated` does it for parts of a `SiteMapPaginator`.
+//!
+//! # Examples
+//!
+//! ```rust,no_run
+//! use sitemap::output::AtomicFile;
+//! use sitemap::writer::SiteMapWriter;
+//! fn main() {
+//!     let output = AtomicFile::create("public/sitemap.xml").expect("Unable to create file.");
+//!     let sitemap_writer = SiteMapWriter::new(output);
+//!     let mut urlwriter = sitemap_writer.start_urlset().expect("Unable to write urlset");
+//!     urlwriter.url("http://github.com").expect("Unable to write url");
+//!     urlwriter.commit().expect("Unable to publish sitemap");
+//! }
+//! ```
+use crate::Error;
+use crate::provider::{UrlProvider, SiteMapPaginator};
+use crate::writer::SiteMapWriter;
+use std::fs;
+use std::io;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use tempfile::NamedTempFile;
+
+/// File which is published atomically on commit.
+pub struct AtomicFile {
+    path: PathBuf,
+    file: BufWriter<NamedTempFile>,
+}
+
+impl AtomicFile {
+    /// Creates temporary file in the directory of `path`.
+    pub fn create<P: AsRef<Path>>(path: P) -> Result<AtomicFile, Error> {
+        let path = path.as_ref().to_path_buf();
+        let mut builder = tempfile::Builder::new();
+        builder.prefix(".sitemap").suffix(".tmp");
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            // Same permissions as `File::create`, temporary files are private by default.
+            builder.permissions(fs::Permissions::from_mode(0o666));
+        }
+        let file = builder.tempfile_in(directory(&path))?;
+        Ok(AtomicFile {
+            path,
+            file: BufWriter::new(file),
+        })
+    }
+
+    /// Returns destination path.
+    pub fn path(&self) -> &Path {
+        return &self.path;
+    }
+
+    /// Flushes and syncs written data, then replaces the destination with it.
+    pub fn commit(self) -> Result<(), Error> {
+        let file = self.file.into_inner().map_err(|error| error.into_error())?;
+        file.as_file().sync_all()?;
+        file.persist(&self.path).map_err(|error| error.error)?;
+        sync_directory(directory(&self.path))?;
+        return Ok(());
+    }
+
+    /// Atomically replaces file at `path` with the content.
+    pub fn publish<P: AsRef<Path>>(path: P, content: &[u8]) -> Result<(), Error> {
+        let mut file = AtomicFile::create(path)?;
+        file.file.write_all(content)?;
+        return file.commit();
+    }
+}
+
+impl Write for AtomicFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        return self.file.write(buf);
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        return self.file.flush();
+    }
+}
+
+/// Publishes every part of the paginator into `directory`, then its index as `index_name`.
+///
+/// The index is written only after all parts were committed,
+/// so a failing part leaves the previous index in place.
+pub fn publish_paginated<P: UrlProvider, D: AsRef<Path>>(
+    paginator: &SiteMapPaginator<P>,
+    directory: D,
+    index_name: &str,
+) -> Result<(), Error> {
+    let directory = directory.as_ref();
+    for part in 1..=paginator.parts()? {
+        let path = directory.join(file_name(&paginator.part_url(part)?)?);
+        publish_part(paginator, part, path)?;
+    }
+    let file = AtomicFile::create(directory.join(index_name))?;
+    let (mut writer, _) = paginator.write_index(SiteMapWriter::new(file))?;
+    writer.flush()?;
+    return writer.into_inner().commit();
+}
+
+fn publish_part<P: UrlProvider>(paginator: &SiteMapPaginator<P>, part: usize, path: PathBuf) -> Result<(), Error> {
+    let file = AtomicFile::create(path)?;
+    #[cfg(feature = "gzip")]
+    {
+        if paginator.is_gzip() {
+            let encoder = flate2::write::GzEncoder::new(file, flate2::Compression::default());
+            let (mut writer, _) = paginator.write_part(part, SiteMapWriter::new(encoder))?;
+            writer.flush()?;
+            return writer.into_inner().finish()?.commit();
+        }
+    }
+    let (mut writer, _) = paginator.write_part(part, SiteMapWriter::new(file))?;
+    writer.flush()?;
+    return writer.into_inner().commit();
+}
+
+/// Returns name of the file at the location.
+pub(crate) fn file_name(location: &url::Url) -> Result<String, Error> {
+    match location.path_segments().and_then(|mut segments| segments.next_back()) {
+        Some(name) if !name.is_empty() => {
+            return Ok(name.to_string());
+        }
+        _ => {
+            return Err(Error::InvalidUrl {
+                value: location.to_string(),
+                error: url::ParseError::RelativeUrlWithCannotBeABaseBase,
+            });
+        }
+    }
+}
+
+fn directory(path: &Path) -> &Path {
+    match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    }
+}
+
+/// Persists the rename in the directory entry.
+#[cfg(unix)]
+fn sync_directory(directory: &Path) -> io::Result<()> {
+    return fs::File::open(directory)?.sync_all();
+}
+
+#[cfg(not(unix))]
+fn sync_directory(_directory: &Path) -> io::Result<()> {
+    return Ok(());
+}
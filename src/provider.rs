@@ -0,0 +1,227 @@
+//! Contains paginated url sources for large dynamic sites.
+//!
+//! `SiteMapPaginator` splits urls of a `UrlProvider` into parts, writes the
+//! sitemap index and each part on its own, querying only the urls of the part.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use sitemap::provider::{MemoryProvider, SiteMapPaginator};
+//! use sitemap::structs::UrlEntry;
+//! use sitemap::writer::SiteMapWriter;
+//! use url::Url;
+//! fn main() {
+//!     let provider = MemoryProvider::new((0..5).map(|id| {
+//!         UrlEntry::from(format!("https://www.example.com/products/{}", id))
+//!     }));
+//!     let paginator = SiteMapPaginator::new(provider, Url::parse("https://www.example.com/").unwrap())
+//!         .part_size(2);
+//!     assert_eq!(paginator.parts().unwrap(), 3);
+//!     let mut index = Vec::new();
+//!     paginator.write_index(SiteMapWriter::new(&mut index)).expect("Unable to write index");
+//!     let mut part = Vec::new();
+//!     paginator.write_part(3, SiteMapWriter::new(&mut part)).expect("Unable to write part");
+//! }
+//! ```
+use crate::Error;
+use crate::structs::{UrlEntry, SiteMapEntry, LastMod};
+use crate::writer::{SiteMapWriter, Ended, MAX_ENTRIES};
+use chrono::{DateTime, FixedOffset};
+use std::io::Write;
+use std::sync::Arc;
+use url::Url;
+
+/// Source of urls which can be queried page by page.
+///
+/// Urls must be returned in a stable order, so that pages do not overlap.
+///
+/// Pages are addressed by offset rather than by cursor: every part is written
+/// on its own, e.g. when a single part is requested over HTTP, and a cursor of
+/// a part is only known after the previous parts were fetched.
+/// Implementations backed by databases may still map offsets to keyset cursors,
+/// e.g. by looking up the key at `offset` with an indexed query.
+pub trait UrlProvider {
+    /// Returns total number of urls.
+    fn count(&self) -> Result<usize, Error>;
+
+    /// Returns at most `limit` urls starting at `offset`.
+    fn fetch(&self, offset: usize, limit: usize) -> Result<Vec<UrlEntry>, Error>;
+
+    /// Returns the latest modification time of urls in `offset..offset + limit`.
+    ///
+    /// Unknown by default, so the index is written without `<lastmod>` and
+    /// without fetching any url. Override it with a cheap aggregate query.
+    fn lastmod(&self, _offset: usize, _limit: usize) -> Result<Option<DateTime<FixedOffset>>, Error> {
+        return Ok(None);
+    }
+}
+
+impl<P: UrlProvider + ?Sized> UrlProvider for Arc<P> {
+    fn count(&self) -> Result<usize, Error> {
+        return (**self).count();
+    }
+
+    fn fetch(&self, offset: usize, limit: usize) -> Result<Vec<UrlEntry>, Error> {
+        return (**self).fetch(offset, limit);
+    }
+
+    fn lastmod(&self, offset: usize, limit: usize) -> Result<Option<DateTime<FixedOffset>>, Error> {
+        return (**self).lastmod(offset, limit);
+    }
+}
+
+/// Provides urls kept in memory.
+#[derive(Clone,Debug,Default)]
+pub struct MemoryProvider {
+    entries: Vec<UrlEntry>,
+}
+
+impl MemoryProvider {
+    /// Creates provider of the entries.
+    pub fn new<I: IntoIterator<Item = UrlEntry>>(entries: I) -> MemoryProvider {
+        MemoryProvider {
+            entries: entries.into_iter().collect(),
+        }
+    }
+
+    fn page(&self, offset: usize, limit: usize) -> &[UrlEntry] {
+        let start = offset.min(self.entries.len());
+        let end = offset.saturating_add(limit).min(self.entries.len());
+        return &self.entries[start..end];
+    }
+}
+
+impl UrlProvider for MemoryProvider {
+    fn count(&self) -> Result<usize, Error> {
+        return Ok(self.entries.len());
+    }
+
+    fn fetch(&self, offset: usize, limit: usize) -> Result<Vec<UrlEntry>, Error> {
+        return Ok(self.page(offset, limit).to_vec());
+    }
+
+    fn lastmod(&self, offset: usize, limit: usize) -> Result<Option<DateTime<FixedOffset>>, Error> {
+        return Ok(self.page(offset, limit).iter().filter_map(|entry| entry.lastmod.get_time()).max());
+    }
+}
+
+/// Ended writer and the latest modification time of the written entries.
+pub type Written<T> = (SiteMapWriter<T, Ended>, Option<DateTime<FixedOffset>>);
+
+/// Splits urls of the provider into sitemap parts.
+///
+/// Parts are named `sitemap-N.xml` (or `sitemap-N.xml.gz`), counting from 1,
+/// and are placed at `base_url`.
+#[derive(Clone,Debug)]
+pub struct SiteMapPaginator<P: UrlProvider> {
+    provider: P,
+    base_url: Url,
+    part_size: usize,
+    gzip: bool,
+}
+
+impl<P: UrlProvider> SiteMapPaginator<P> {
+    /// Creates paginator of the provider.
+    /// `base_url` is the directory of the parts and should end with a slash.
+    pub fn new(provider: P, base_url: Url) -> SiteMapPaginator<P> {
+        SiteMapPaginator {
+            provider,
+            base_url,
+            part_size: MAX_ENTRIES,
+            gzip: false,
+        }
+    }
+
+    /// Defines number of urls in a part, at most `MAX_ENTRIES`.
+    pub fn part_size(mut self, part_size: usize) -> SiteMapPaginator<P> {
+        self.part_size = part_size.clamp(1, MAX_ENTRIES);
+        return self;
+    }
+
+    /// Links gzipped parts from the index. Requires `gzip` feature.
+    #[cfg(feature = "gzip")]
+    pub fn gzip(mut self, gzip: bool) -> SiteMapPaginator<P> {
+        self.gzip = gzip;
+        return self;
+    }
+
+    /// Checks are parts gzipped.
+    pub fn is_gzip(&self) -> bool {
+        return self.gzip;
+    }
+
+    /// Returns the provider.
+    pub fn provider(&self) -> &P {
+        return &self.provider;
+    }
+
+    /// Returns number of parts.
+    pub fn parts(&self) -> Result<usize, Error> {
+        return Ok(self.provider.count()?.div_ceil(self.part_size));
+    }
+
+    /// Returns url of the part.
+    pub fn part_url(&self, part: usize) -> Result<Url, Error> {
+        let extension = if self.gzip { "xml.gz" } else { "xml" };
+        let location = format!("sitemap-{}.{}", part, extension);
+        match self.base_url.join(&location) {
+            Ok(url) => {
+                return Ok(url);
+            }
+            Err(error) => {
+                return Err(Error::InvalidUrl { value: location, error });
+            }
+        }
+    }
+
+    /// Returns the latest modification time of urls of the part.
+    /// Fails with `Error::InvalidPart` for parts out of `1..=parts()`.
+    pub fn part_lastmod(&self, part: usize) -> Result<Option<DateTime<FixedOffset>>, Error> {
+        return self.provider.lastmod(self.offset(part)?, self.part_size);
+    }
+
+    /// Writes sitemap index listing every part.
+    /// Returns the ended writer and the latest modification time of the parts.
+    pub fn write_index<T: Write + Sized>(
+        &self,
+        writer: SiteMapWriter<T>,
+    ) -> Result<Written<T>, Error> {
+        let mut sitemap_index_writer = writer.start_sitemapindex()?;
+        let mut latest = None;
+        for part in 1..=self.parts()? {
+            let mut entry = SiteMapEntry::from(self.part_url(part)?);
+            if let Some(lastmod) = self.provider.lastmod((part - 1) * self.part_size, self.part_size)? {
+                entry.lastmod = LastMod::DateTime(lastmod);
+                latest = latest.max(Some(lastmod));
+            }
+            sitemap_index_writer.sitemap(entry)?;
+        }
+        return Ok((sitemap_index_writer.end()?, latest));
+    }
+
+    /// Writes urls of the part, counting from 1.
+    /// Fails with `Error::InvalidPart` for parts out of `1..=parts()`.
+    /// Returns the ended writer and the latest modification time of the urls.
+    pub fn write_part<T: Write + Sized>(
+        &self,
+        part: usize,
+        writer: SiteMapWriter<T>,
+    ) -> Result<Written<T>, Error> {
+        let offset = self.offset(part)?;
+        let mut urlwriter = writer.start_urlset()?;
+        let mut latest = None;
+        for entry in self.provider.fetch(offset, self.part_size)? {
+            latest = latest.max(entry.lastmod.get_time());
+            urlwriter.url(entry)?;
+        }
+        return Ok((urlwriter.end()?, latest));
+    }
+
+    fn offset(&self, part: usize) -> Result<usize, Error> {
+        let parts = self.parts()?;
+        if part == 0 || part > parts {
+            return Err(Error::InvalidPart { part, parts });
+        }
+        return Ok((part - 1) * self.part_size);
+    }
+}
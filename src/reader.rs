@@ -9,33 +9,199 @@
 //! use std::fs::File;
 //! fn main() {
 //!    let mut urls = Vec::new();
-//! 	let mut sitemaps = Vec::new();
-//! 	let mut errors = Vec::new();
-//! 	let file = File::open("tests/documents/sitemap1.xml").expect("Unable to open file.");
-//! 	let parser = SiteMapReader::new(file);
-//! 	for entity in parser {
-//! 		match entity {
-//! 			SiteMapEntity::Url(url_entry) => {
-//! 				urls.push(url_entry);
-//! 			},
-//! 			SiteMapEntity::SiteMap(sitemap_entry) => {
-//! 				sitemaps.push(sitemap_entry);
-//! 			},
-//! 			SiteMapEntity::Err(error) => {
-//! 				errors.push(error);
-//! 			},
-//! 		}
-//! 	}
-//! 	println!("urls = {:?}",urls);
-//! 	println!("sitemaps = {:?}",sitemaps);
-//! 	println!("errors = {:?}",errors);
+//!     let mut sitemaps = Vec::new();
+//!     let mut errors = Vec::new();
+//!     let file = File::open("tests/documents/sitemap1.xml").expect("Unable to open file.");
+//!     let parser = SiteMapReader::new(file);
+//!     for entity in parser {
+//!         match entity {
+//!             SiteMapEntity::Url(url_entry) => {
+//!                 urls.push(url_entry);
+//!             },
+//!             SiteMapEntity::SiteMap(sitemap_entry) => {
+//!                 sitemaps.push(sitemap_entry);
+//!             },
+//!             SiteMapEntity::Err(error) => {
+//!                 errors.push(error);
+//!             },
+//!             _ => {
+//!                 // Returned only by readers with scope or validation.
+//!             },
+//!         }
+//!     }
+//!     println!("urls = {:?}",urls);
+//!     println!("sitemaps = {:?}",sitemaps);
+//!     println!("errors = {:?}",errors);
 //! }
 //! ```
 use crate::structs;
-use xml;
-use std::io::Read;
-use xml::reader::{EventReader, XmlEvent, Events};
-use std::convert::From;
+use crate::{Error, Limit};
+use crate::scope::SiteMapScope;
+use crate::writer::{MAX_BYTES, MAX_ENTRIES};
+use std::io::{self, BufRead, BufReader, Read};
+use crate::backend::{Event, EventReader};
+use crate::encoding::DecodeReader;
+
+/// Limits of the read document, protecting readers from hostile input.
+///
+/// Default limits accept every document within the protocol limits:
+/// `MAX_BYTES` bytes, `MAX_ENTRIES` entries, 32 nested elements,
+/// 65536 bytes of text and 64 attributes per element.
+/// Exceeding a limit is reported as `Error::LimitExceeded` and ends reading.
+/// Readers apply the default limits since 0.5, `unlimited()` restores reading
+/// trusted documents of any size.
+#[derive(Clone,Debug,PartialEq,Eq)]
+pub struct ReaderLimits {
+    max_bytes: usize,
+    max_depth: usize,
+    max_field_length: usize,
+    max_entries: usize,
+    max_attributes: usize,
+}
+
+impl Default for ReaderLimits {
+    fn default() -> ReaderLimits {
+        ReaderLimits {
+            max_bytes: MAX_BYTES,
+            max_depth: 32,
+            max_field_length: 64 * 1024,
+            max_entries: MAX_ENTRIES,
+            max_attributes: 64,
+        }
+    }
+}
+
+impl ReaderLimits {
+    /// Creates default limits.
+    pub fn new() -> ReaderLimits {
+        return ReaderLimits::default();
+    }
+
+    /// Creates limits accepting documents of any size, only for trusted input.
+    pub fn unlimited() -> ReaderLimits {
+        return ReaderLimits {
+            max_bytes: usize::MAX,
+            max_depth: usize::MAX,
+            max_field_length: usize::MAX,
+            max_entries: usize::MAX,
+            max_attributes: usize::MAX,
+        };
+    }
+
+    /// Defines maximum size of the document in bytes, as read from the stream.
+    pub fn max_bytes(mut self, max_bytes: usize) -> ReaderLimits {
+        self.max_bytes = max_bytes;
+        return self;
+    }
+
+    /// Defines maximum nesting depth of elements, the root element has depth 1.
+    pub fn max_depth(mut self, max_depth: usize) -> ReaderLimits {
+        self.max_depth = max_depth;
+        return self;
+    }
+
+    /// Defines maximum length of text in bytes, e.g. of the url location.
+    pub fn max_field_length(mut self, max_field_length: usize) -> ReaderLimits {
+        self.max_field_length = max_field_length;
+        return self;
+    }
+
+    /// Defines maximum number of entries in the document.
+    pub fn max_entries(mut self, max_entries: usize) -> ReaderLimits {
+        self.max_entries = max_entries;
+        return self;
+    }
+
+    /// Defines maximum number of attributes of an element, namespace declarations excluded.
+    pub fn max_attributes(mut self, max_attributes: usize) -> ReaderLimits {
+        self.max_attributes = max_attributes;
+        return self;
+    }
+
+    pub(crate) fn check_depth(&self, depth: usize) -> Result<(), Error> {
+        return check(depth, self.max_depth, Limit::Depth);
+    }
+
+    pub(crate) fn check_field_length(&self, length: usize) -> Result<(), Error> {
+        return check(length, self.max_field_length, Limit::FieldLength);
+    }
+
+    pub(crate) fn check_entries(&self, entries: usize) -> Result<(), Error> {
+        return check(entries, self.max_entries, Limit::Entries);
+    }
+
+    pub(crate) fn check_attributes(&self, attributes: usize) -> Result<(), Error> {
+        return check(attributes, self.max_attributes, Limit::Attributes);
+    }
+}
+
+fn check(value: usize, max: usize, limit: fn(usize) -> Limit) -> Result<(), Error> {
+    if value > max {
+        return Err(Error::LimitExceeded(limit(max)));
+    }
+    return Ok(());
+}
+
+/// Stops reading the stream after the limit of bytes.
+pub(crate) struct LimitedRead<R: BufRead> {
+    inner: R,
+    max_bytes: usize,
+    bytes: usize,
+}
+
+impl<R: BufRead> LimitedRead<R> {
+    pub(crate) fn new(inner: R) -> LimitedRead<R> {
+        LimitedRead { inner, max_bytes: MAX_BYTES, bytes: 0 }
+    }
+
+    pub(crate) fn set_limits(&mut self, limits: &ReaderLimits) {
+        self.max_bytes = limits.max_bytes;
+    }
+
+    #[cfg(feature = "quick-xml")]
+    pub(crate) fn get_ref(&self) -> &R {
+        return &self.inner;
+    }
+}
+
+impl<R: BufRead> Read for LimitedRead<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let available = self.fill_buf()?;
+        let len = available.len().min(buf.len());
+        buf[..len].copy_from_slice(&available[..len]);
+        self.consume(len);
+        return Ok(len);
+    }
+}
+
+impl<R: BufRead> BufRead for LimitedRead<R> {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        let max_bytes = self.max_bytes;
+        let remaining = max_bytes.saturating_sub(self.bytes);
+        let available = self.inner.fill_buf()?;
+        if remaining == 0 && !available.is_empty() {
+            let error = Error::LimitExceeded(Limit::Bytes(max_bytes));
+            return Err(io::Error::new(io::ErrorKind::InvalidData, error));
+        }
+        let len = available.len().min(remaining);
+        return Ok(&available[..len]);
+    }
+
+    fn consume(&mut self, amt: usize) {
+        self.bytes += amt;
+        self.inner.consume(amt);
+    }
+}
+
+/// Paths of elements containing entry fields.
+const FIELDS: [&[&str]; 6] = [
+    &["urlset", "url", "loc"],
+    &["urlset", "url", "lastmod"],
+    &["urlset", "url", "changefreq"],
+    &["urlset", "url", "priority"],
+    &["sitemapindex", "sitemap", "loc"],
+    &["sitemapindex", "sitemap", "lastmod"],
+];
 
 /// A wrapper around an `std::io::Read` instance which provides sitemap parsing.
 ///
@@ -44,7 +210,13 @@ pub struct SiteMapReader<T: Read + Sized> {
     path: Vec<String>,
     url_item: structs::UrlEntry,
     sitemap_item: structs::SiteMapEntry,
-    parser: Events<T>,
+    entries: EntryChecker,
+    // Text of the current field, which may come in several chunks of text and CDATA.
+    text: String,
+    limits: ReaderLimits,
+    entries_read: usize,
+    failed: bool,
+    parser: EventReader<DecodeReader<LimitedRead<BufReader<T>>>>,
 }
 /// Sitemap entry.
 #[derive(Debug,Clone)]
@@ -53,47 +225,263 @@ pub enum SiteMapEntity {
     Url(structs::UrlEntry),
     /// Sitemap entry.
     SiteMap(structs::SiteMapEntry),
-    /// XML read error.
-    Err(xml::reader::Error),
+    /// Url entry out of the sitemap scope.
+    OutOfScope(structs::UrlEntry),
+    /// Url entry with invalid fields. Returned only when validation is enabled.
+    InvalidUrl {
+        /// Url entry.
+        entry: structs::UrlEntry,
+        /// Every problem of the entry.
+        problems: Vec<Error>,
+    },
+    /// Sitemap entry with invalid fields. Returned only when validation is enabled.
+    InvalidSiteMap {
+        /// Sitemap entry.
+        entry: structs::SiteMapEntry,
+        /// Every problem of the entry.
+        problems: Vec<Error>,
+    },
+    /// Read error.
+    Err(Error),
+}
+
+/// Parses field values and checks complete entries against scope and validation rules.
+#[derive(Default)]
+pub(crate) struct EntryChecker {
+    scope: Option<SiteMapScope>,
+    validate: bool,
+    root_namespace: Option<String>,
+    problems: Vec<Error>,
+}
+
+impl EntryChecker {
+    pub(crate) fn set_scope(&mut self, scope: SiteMapScope) {
+        self.scope = Some(scope);
+    }
+    pub(crate) fn set_validation(&mut self) {
+        self.validate = true;
+    }
+    /// Forgets problems of the previous entry.
+    pub(crate) fn start_entry(&mut self) {
+        self.problems.clear();
+    }
+    /// Remembers namespace of the root element, e.g. of a legacy sitemap schema.
+    pub(crate) fn root_namespace(&mut self, namespace: Option<&str>) {
+        self.root_namespace = namespace.map(str::to_string);
+    }
+    /// Checks namespace of a child element of the entry, other namespaces than
+    /// the one of the root element belong to extensions, which are not supported.
+    pub(crate) fn child_namespace(&mut self, namespace: &str) {
+        if !self.validate || self.root_namespace.as_deref() == Some(namespace) {
+            return;
+        }
+        let reported = self.problems.iter().any(|problem| {
+            matches!(problem, Error::UnsupportedExtension { namespace: reported } if reported == namespace)
+        });
+        if !reported {
+            self.problems.push(Error::UnsupportedExtension { namespace: namespace.to_string() });
+        }
+    }
+    pub(crate) fn location(&mut self, data: String) -> structs::Location {
+        if !self.validate {
+            return structs::Location::from(data);
+        }
+        let location = structs::Location::from(data.clone());
+        if let structs::Location::ParseErr(error) = location {
+            self.problems.push(Error::InvalidUrl { value: data, error });
+        }
+        return location;
+    }
+    pub(crate) fn lastmod(&mut self, data: String) -> structs::LastMod {
+        if !self.validate {
+            return structs::LastMod::from(data);
+        }
+        let lastmod = structs::LastMod::from(data.clone());
+        if let structs::LastMod::ParseErr(error) = lastmod {
+            self.problems.push(Error::InvalidLastMod { value: data, error });
+        }
+        return lastmod;
+    }
+    pub(crate) fn changefreq(&mut self, data: String) -> structs::ChangeFreq {
+        if !self.validate {
+            return structs::ChangeFreq::from(data);
+        }
+        let changefreq = structs::ChangeFreq::from(data.clone());
+        if let structs::ChangeFreq::ParseErr(_) = changefreq {
+            self.problems.push(Error::InvalidChangeFreq { value: data });
+        }
+        return changefreq;
+    }
+    pub(crate) fn priority(&mut self, data: String) -> structs::Priority {
+        if !self.validate {
+            return structs::Priority::from(data);
+        }
+        let priority = structs::Priority::from(data.clone());
+        if priority.get_priority().is_none() {
+            self.problems.push(Error::InvalidPriority { value: data });
+        }
+        return priority;
+    }
+    /// Returns entity of the complete url entry.
+    pub(crate) fn url(&mut self, entry: structs::UrlEntry) -> SiteMapEntity {
+        if let (Some(scope), structs::Location::Url(loc)) = (&self.scope, &entry.loc) {
+            if !scope.contains(loc) {
+                if !self.validate {
+                    return SiteMapEntity::OutOfScope(entry);
+                }
+                self.problems.push(Error::ScopeViolation(loc.clone()));
+            }
+        }
+        if self.validate && entry.loc.is_none() {
+            self.problems.push(Error::MissingLocation);
+        }
+        if !self.problems.is_empty() {
+            return SiteMapEntity::InvalidUrl {
+                entry,
+                problems: std::mem::take(&mut self.problems),
+            };
+        }
+        return SiteMapEntity::Url(entry);
+    }
+    /// Returns entity of the complete sitemap entry.
+    pub(crate) fn sitemap(&mut self, entry: structs::SiteMapEntry) -> SiteMapEntity {
+        if self.validate && entry.loc.is_none() {
+            self.problems.push(Error::MissingLocation);
+        }
+        if !self.problems.is_empty() {
+            return SiteMapEntity::InvalidSiteMap {
+                entry,
+                problems: std::mem::take(&mut self.problems),
+            };
+        }
+        return SiteMapEntity::SiteMap(entry);
+    }
 }
+
 impl<T: Read + Sized> SiteMapReader<T> {
     /// Creates a new reader, consuming the given stream.
     pub fn new(source: T) -> SiteMapReader<T> {
-        let parser = EventReader::new(source).into_iter();
+        let parser = EventReader::new(DecodeReader::new(LimitedRead::new(BufReader::new(source))));
         SiteMapReader {
             path: Vec::new(),
             url_item: structs::UrlEntry::new(),
             sitemap_item: structs::SiteMapEntry::new(),
-            parser: parser,
+            entries: EntryChecker::default(),
+            text: String::new(),
+            limits: ReaderLimits::default(),
+            entries_read: 0,
+            failed: false,
+            parser,
         }
     }
+
+    /// Replaces default limits of the read document.
+    pub fn with_limits(mut self, limits: ReaderLimits) -> SiteMapReader<T> {
+        self.parser.get_mut().get_mut().set_limits(&limits);
+        self.limits = limits;
+        return self;
+    }
+
+    /// Checks urls against the scope of the sitemap.
+    /// Urls out of scope are returned as `SiteMapEntity::OutOfScope`.
+    pub fn with_scope(mut self, scope: SiteMapScope) -> SiteMapReader<T> {
+        self.entries.set_scope(scope);
+        return self;
+    }
+
+    /// Validates entries.
+    /// Entries with invalid or missing fields are returned as `SiteMapEntity::InvalidUrl`
+    /// and `SiteMapEntity::InvalidSiteMap` listing every problem of the entry.
+    /// Urls out of scope are reported as `Error::ScopeViolation` problem,
+    /// children in other namespaces than the sitemap one as `Error::UnsupportedExtension`.
+    pub fn with_validation(mut self) -> SiteMapReader<T> {
+        self.entries.set_validation();
+        return self;
+    }
+    /// Returns true inside an element of an entry field.
+    fn in_field(&self) -> bool {
+        return FIELDS.iter().any(|field| self.path == *field);
+    }
     fn open_tag(&mut self) {
-        if self.path == vec!["urlset", "url"] {
+        if self.in_field() {
+            self.text.clear();
+        } else if self.path == vec!["urlset", "url"] {
             self.url_item = structs::UrlEntry::new();
+            self.entries.start_entry();
         } else if self.path == vec!["sitemapindex", "sitemap"] {
             self.sitemap_item = structs::SiteMapEntry::new();
+            self.entries.start_entry();
         }
     }
-    fn text_content(&mut self, data: String) {
+    /// Parses text of the closed field, whitespace only text is ignored.
+    fn close_field(&mut self) {
+        if self.text.trim().is_empty() {
+            return;
+        }
+        let data = std::mem::take(&mut self.text);
         if self.path == vec!["urlset", "url", "loc"] {
-            self.url_item.loc = structs::Location::from(data);
+            self.url_item.loc = self.entries.location(data);
         } else if self.path == vec!["urlset", "url", "lastmod"] {
-            self.url_item.lastmod = structs::LastMod::from(data);
+            self.url_item.lastmod = self.entries.lastmod(data);
         } else if self.path == vec!["urlset", "url", "changefreq"] {
-            self.url_item.changefreq = structs::ChangeFreq::from(data);
+            self.url_item.changefreq = self.entries.changefreq(data);
         } else if self.path == vec!["urlset", "url", "priority"] {
-            self.url_item.priority = structs::Priority::from(data);
+            self.url_item.priority = self.entries.priority(data);
         } else if self.path == vec!["sitemapindex", "sitemap", "loc"] {
-            self.sitemap_item.loc = structs::Location::from(data);
+            self.sitemap_item.loc = self.entries.location(data);
         } else if self.path == vec!["sitemapindex", "sitemap", "lastmod"] {
-            self.sitemap_item.lastmod = structs::LastMod::from(data);
+            self.sitemap_item.lastmod = self.entries.lastmod(data);
+        }
+    }
+    fn next_entity(&mut self) -> Result<Option<SiteMapEntity>, Error> {
+        loop {
+            match self.parser.next() {
+                Some(Ok(Event::Start { name, namespace, attributes })) => {
+                    self.limits.check_depth(self.path.len() + 1)?;
+                    self.limits.check_attributes(attributes)?;
+                    if self.path.is_empty() {
+                        self.entries.root_namespace(namespace.as_deref());
+                    } else if let Some(namespace) = namespace {
+                        if self.path == vec!["urlset", "url"] || self.path == vec!["sitemapindex", "sitemap"] {
+                            self.entries.child_namespace(&namespace);
+                        }
+                    }
+                    self.path.push(name.to_lowercase());
+                    self.open_tag();
+                }
+                Some(Ok(Event::End)) => {
+                    if self.in_field() {
+                        self.close_field();
+                    }
+                    let entity = self.close_tag();
+                    self.path.pop();
+                    if let Some(entity) = entity {
+                        self.entries_read += 1;
+                        self.limits.check_entries(self.entries_read)?;
+                        return Ok(Some(entity));
+                    }
+                }
+                Some(Ok(Event::Text(data))) => {
+                    self.limits.check_field_length(data.len())?;
+                    if self.in_field() {
+                        self.text.push_str(&data);
+                        self.limits.check_field_length(self.text.len())?;
+                    }
+                }
+                Some(Err(error)) => {
+                    return Err(error);
+                }
+                None => {
+                    return Ok(None);
+                }
+            }
         }
     }
     fn close_tag(&mut self) -> Option<SiteMapEntity> {
         if self.path == vec!["urlset", "url"] {
-            return Some(SiteMapEntity::Url(self.url_item.clone()));
+            return Some(self.entries.url(self.url_item.clone()));
         } else if self.path == vec!["sitemapindex", "sitemap"] {
-            return Some(SiteMapEntity::SiteMap(self.sitemap_item.clone()));
+            return Some(self.entries.sitemap(self.sitemap_item.clone()));
         }
         return None;
     }
@@ -101,38 +489,16 @@ impl<T: Read + Sized> SiteMapReader<T> {
 impl<T: Read + Sized> Iterator for SiteMapReader<T> {
     type Item = SiteMapEntity;
     fn next(&mut self) -> Option<SiteMapEntity> {
-        loop {
-            let e = self.parser.next();
-            match e {
-                Some(e) => {
-                    match e {
-                        Ok(XmlEvent::StartElement { name, .. }) => {
-                            let tag_name = name.local_name.to_lowercase();
-                            self.path.push(tag_name.to_string());
-                            self.open_tag();
-                        }
-                        Ok(XmlEvent::EndElement { .. }) => {
-                            let entity = self.close_tag();
-                            self.path.pop();
-                            match entity {
-                                Some(entity) => {
-                                    return Some(entity);
-                                }
-                                None => {}
-                            }
-                        }
-                        Ok(XmlEvent::Characters(data)) => {
-                            self.text_content(data);
-                        }
-                        Err(error) => {
-                            return Some(SiteMapEntity::Err(error));
-                        }
-                        _ => {}
-                    }
-                }
-                None => {
-                    return None;
-                }
+        if self.failed {
+            return None;
+        }
+        match self.next_entity() {
+            Ok(entity) => {
+                return entity;
+            }
+            Err(error) => {
+                self.failed = true;
+                return Some(SiteMapEntity::Err(error));
             }
         }
     }
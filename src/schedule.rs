@@ -0,0 +1,154 @@
+//! Contains recrawl scheduling helpers.
+//!
+//! An entry is due right away when it was never crawled or was modified after
+//! the last crawl. Otherwise it is due after the interval of its `changefreq`
+//! since the last crawl, and never for archived (`never`) urls.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use sitemap::schedule::{Schedule, CrawlEntry, Due};
+//! use sitemap::structs::{UrlEntry, ChangeFreq};
+//! use chrono::DateTime;
+//! fn main() {
+//!     let crawled = DateTime::parse_from_rfc3339("2020-01-01T00:00:00+00:00").unwrap();
+//!     let schedule = Schedule::new();
+//!     let daily = UrlEntry::builder().loc("http://www.example.com/news").changefreq(ChangeFreq::Daily).build().unwrap();
+//!     let weekly = UrlEntry::builder().loc("http://www.example.com/about").changefreq(ChangeFreq::Weekly).build().unwrap();
+//!     let fresh = UrlEntry::from("http://www.example.com/new");
+//!     let mut queue = vec![
+//!         schedule.entry(weekly, Some(crawled)),
+//!         schedule.entry(daily, Some(crawled)),
+//!         schedule.entry(fresh, None),
+//!     ];
+//!     queue.sort();
+//!     assert_eq!(queue[0].due, Due::Immediately);
+//!     assert_eq!(queue[1].entry.loc.to_string(), "http://www.example.com/news");
+//!     assert_eq!(queue[2].entry.loc.to_string(), "http://www.example.com/about");
+//! }
+//! ```
+use crate::structs::{UrlEntry, ChangeFreq, LastMod, PriorityValue};
+use chrono::{DateTime, FixedOffset, Duration};
+use std::cmp::Ordering;
+
+/// Time when an entry should be crawled.
+///
+/// Values are ordered from the most urgent: `Immediately`, then by time, then `Never`.
+#[derive(Clone,Copy,Debug,PartialEq,Eq,PartialOrd,Ord)]
+pub enum Due {
+    /// Entry was never crawled.
+    Immediately,
+    /// Entry is due at the given time.
+    At(DateTime<FixedOffset>),
+    /// Entry should not be crawled again.
+    Never,
+}
+
+impl Due {
+    /// Checks is entry due at the given time.
+    pub fn is_due(&self, now: DateTime<FixedOffset>) -> bool {
+        match *self {
+            Due::Immediately => true,
+            Due::At(time) => time <= now,
+            Due::Never => false,
+        }
+    }
+}
+
+/// Recrawl schedule.
+#[derive(Clone,Debug)]
+pub struct Schedule {
+    default_interval: Duration,
+}
+
+impl Schedule {
+    /// Creates schedule recrawling entries without `changefreq` daily.
+    pub fn new() -> Schedule {
+        Schedule {
+            default_interval: Duration::days(1),
+        }
+    }
+
+    /// Defines interval for entries without `changefreq` or with invalid one.
+    pub fn default_interval(mut self, interval: Duration) -> Schedule {
+        self.default_interval = interval;
+        return self;
+    }
+
+    /// Computes next crawl time of the entry crawled last at `last_crawled`.
+    ///
+    /// Entries modified after the last crawl are due at their modification time.
+    pub fn next_due(&self, entry: &UrlEntry, last_crawled: Option<DateTime<FixedOffset>>) -> Due {
+        let last_crawled = match last_crawled {
+            Some(last_crawled) => last_crawled,
+            None => {
+                return Due::Immediately;
+            }
+        };
+        if let LastMod::DateTime(lastmod) = entry.lastmod {
+            if lastmod > last_crawled {
+                return Due::At(lastmod);
+            }
+        }
+        let interval = match entry.changefreq {
+            ChangeFreq::Never => {
+                return Due::Never;
+            }
+            ChangeFreq::None | ChangeFreq::ParseErr(_) => self.default_interval,
+            ref changefreq => changefreq.interval().unwrap_or(self.default_interval),
+        };
+        match last_crawled.checked_add_signed(interval) {
+            Some(time) => {
+                return Due::At(time);
+            }
+            None => {
+                return Due::Never;
+            }
+        }
+    }
+
+    /// Creates crawl queue item of the entry.
+    pub fn entry(&self, entry: UrlEntry, last_crawled: Option<DateTime<FixedOffset>>) -> CrawlEntry {
+        let due = self.next_due(&entry, last_crawled);
+        return CrawlEntry { entry, due };
+    }
+}
+
+impl Default for Schedule {
+    fn default() -> Schedule {
+        return Schedule::new();
+    }
+}
+
+/// Crawl queue item.
+///
+/// Items are ordered by urgency, the most urgent first: by due time, then by
+/// descending priority (entries without priority count as `0.5`), then by entry.
+/// Wrap items into `std::cmp::Reverse` to pop the most urgent from `BinaryHeap`.
+#[derive(Clone,Debug,PartialEq,Eq)]
+pub struct CrawlEntry {
+    /// Url entry.
+    pub entry: UrlEntry,
+    /// Next crawl time.
+    pub due: Due,
+}
+
+impl CrawlEntry {
+    fn priority(&self) -> PriorityValue {
+        return self.entry.priority.get_value().unwrap_or(PriorityValue::DEFAULT);
+    }
+}
+
+impl PartialOrd for CrawlEntry {
+    fn partial_cmp(&self, other: &CrawlEntry) -> Option<Ordering> {
+        return Some(self.cmp(other));
+    }
+}
+
+impl Ord for CrawlEntry {
+    fn cmp(&self, other: &CrawlEntry) -> Ordering {
+        return self.due.cmp(&other.due)
+            .then_with(|| other.priority().cmp(&self.priority()))
+            .then_with(|| self.entry.cmp(&other.entry));
+    }
+}
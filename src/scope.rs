@@ -0,0 +1,66 @@
+//! Contains sitemap scope checks.
+//!
+//! A sitemap located at `http://example.com/catalog/sitemap.xml` may only list
+//! urls starting with `http://example.com/catalog/`. Hosts which reference the
+//! sitemap from their `robots.txt` (cross submission) may be listed entirely.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use sitemap::scope::SiteMapScope;
+//! use url::Url;
+//! fn main() {
+//!     let scope = SiteMapScope::new(Url::parse("http://example.com/catalog/sitemap.xml").unwrap())
+//!         .cross_submit(Url::parse("http://example.org/robots.txt").unwrap());
+//!     assert!(scope.contains(&Url::parse("http://example.com/catalog/item1").unwrap()));
+//!     assert!(scope.contains(&Url::parse("http://example.org/item2").unwrap()));
+//!     assert!(!scope.contains(&Url::parse("http://example.com/item3").unwrap()));
+//!     assert!(!scope.contains(&Url::parse("https://example.com/catalog/item4").unwrap()));
+//! }
+//! ```
+use url::{Url, Origin};
+
+/// Set of urls which may be listed in the sitemap.
+#[derive(Clone,Debug)]
+pub struct SiteMapScope {
+    location: Url,
+    directory: String,
+    cross_submits: Vec<Origin>,
+}
+
+impl SiteMapScope {
+    /// Creates scope of sitemap placed at `location`.
+    pub fn new(location: Url) -> SiteMapScope {
+        let path = location.path();
+        let directory = match path.rfind('/') {
+            Some(index) => path[..index + 1].to_string(),
+            None => "/".to_string(),
+        };
+        SiteMapScope {
+            location,
+            directory,
+            cross_submits: Vec::new(),
+        }
+    }
+
+    /// Allows any url of the host whose `robots.txt` (placed at `robots_txt`)
+    /// references the sitemap.
+    pub fn cross_submit(mut self, robots_txt: Url) -> SiteMapScope {
+        self.cross_submits.push(robots_txt.origin());
+        return self;
+    }
+
+    /// Returns sitemap location.
+    pub fn location(&self) -> &Url {
+        return &self.location;
+    }
+
+    /// Checks is url allowed to be listed in the sitemap.
+    pub fn contains(&self, url: &Url) -> bool {
+        let origin = url.origin();
+        if origin == self.location.origin() && url.path().starts_with(&self.directory) {
+            return true;
+        }
+        return self.cross_submits.iter().any(|allowed| allowed == &origin);
+    }
+}
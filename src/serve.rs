@@ -0,0 +1,225 @@
+//! Contains axum integration serving sitemaps. Requires `axum` feature.
+//!
+//! The router serves the index at `sitemap.xml` and parts at `sitemap-N.xml`,
+//! counting from 1. Every document is also served gzipped with `.gz` suffix.
+//! Plain documents are compressed when the client accepts gzip encoding.
+//!
+//! Documents are generated on demand from a [`UrlProvider`](../provider/trait.UrlProvider.html),
+//! a request for a part queries only the urls of the part.
+//! The provider is queried on the blocking thread pool of tokio, so it may block.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use sitemap::serve::SiteMapService;
+//! use sitemap::structs::UrlEntry;
+//! use url::Url;
+//! fn main() {
+//!     let entries = vec![
+//!         UrlEntry::from("https://www.example.com/"),
+//!         UrlEntry::from("https://www.example.com/about.html"),
+//!     ];
+//!     let service = SiteMapService::new(Url::parse("https://www.example.com/").unwrap(), entries)
+//!         .part_size(1000);
+//!     let router: axum::Router = axum::Router::new().merge(service.router());
+//! }
+//! ```
+use crate::Error;
+use crate::content;
+use crate::structs::UrlEntry;
+use crate::writer::SiteMapWriter;
+use crate::provider::{UrlProvider, MemoryProvider, SiteMapPaginator};
+use axum::Router;
+use axum::body::Body;
+use axum::extract::{Path, State};
+use axum::http::{header, HeaderMap, HeaderValue, StatusCode};
+use axum::response::Response;
+use axum::routing::get;
+use chrono::{DateTime, FixedOffset, Utc};
+use std::fmt;
+use std::sync::Arc;
+use url::Url;
+
+/// Serves urls as a sitemap index and its parts.
+#[derive(Clone)]
+pub struct SiteMapService {
+    paginator: SiteMapPaginator<Arc<dyn UrlProvider + Send + Sync>>,
+}
+
+/// Error failing a sitemap request.
+///
+/// Responses with status 500 carry it in their extensions with an empty body,
+/// so the application may log it from a middleware.
+#[derive(Clone,Debug)]
+pub struct ServeError(pub Arc<Error>);
+
+struct Document {
+    body: Vec<u8>,
+    lastmod: Option<DateTime<FixedOffset>>,
+}
+
+impl SiteMapService {
+    /// Creates service of the entries.
+    /// `base_url` is the url the router is mounted at and should end with a slash.
+    pub fn new<I: IntoIterator<Item = UrlEntry>>(base_url: Url, entries: I) -> SiteMapService {
+        return SiteMapService::with_provider(base_url, MemoryProvider::new(entries));
+    }
+
+    /// Creates service of the urls of the provider.
+    /// `base_url` is the url the router is mounted at and should end with a slash.
+    pub fn with_provider<P: UrlProvider + Send + Sync + 'static>(base_url: Url, provider: P) -> SiteMapService {
+        let provider: Arc<dyn UrlProvider + Send + Sync> = Arc::new(provider);
+        SiteMapService {
+            paginator: SiteMapPaginator::new(provider, base_url),
+        }
+    }
+
+    /// Defines number of urls in a part, at most `MAX_ENTRIES`.
+    pub fn part_size(mut self, part_size: usize) -> SiteMapService {
+        self.paginator = self.paginator.part_size(part_size);
+        return self;
+    }
+
+    /// Links gzipped parts from the index.
+    pub fn gzip(mut self, gzip: bool) -> SiteMapService {
+        self.paginator = self.paginator.gzip(gzip);
+        return self;
+    }
+
+    /// Returns number of parts.
+    pub fn parts(&self) -> Result<usize, Error> {
+        return self.paginator.parts();
+    }
+
+    /// Creates router serving the sitemap.
+    pub fn router(self) -> Router {
+        return Router::new()
+            .route("/{file}", get(serve))
+            .with_state(Arc::new(self));
+    }
+
+    fn index(&self) -> Result<Document, Error> {
+        let mut body = Vec::new();
+        let (_, lastmod) = self.paginator.write_index(SiteMapWriter::new(&mut body))?;
+        return Ok(Document { body, lastmod });
+    }
+
+    fn part(&self, part: usize) -> Result<Document, Error> {
+        let mut body = Vec::new();
+        let (_, lastmod) = self.paginator.write_part(part, SiteMapWriter::new(&mut body))?;
+        return Ok(Document { body, lastmod });
+    }
+
+    fn document(&self, name: &str) -> Option<Result<Document, Error>> {
+        if name == "sitemap.xml" {
+            return Some(self.index());
+        }
+        let number = name.strip_prefix("sitemap-")?.strip_suffix(".xml")?;
+        let part = number.parse::<usize>().ok()?;
+        if part.to_string() != number {
+            return None;
+        }
+        match self.part(part) {
+            Err(Error::InvalidPart { .. }) => {
+                return None;
+            }
+            document => {
+                return Some(document);
+            }
+        }
+    }
+}
+
+impl fmt::Debug for SiteMapService {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("SiteMapService").finish_non_exhaustive()
+    }
+}
+
+fn accepts_gzip(headers: &HeaderMap) -> bool {
+    return headers.get_all(header::ACCEPT_ENCODING).iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .any(|coding| {
+            let mut params = coding.split(';').map(str::trim);
+            let name = params.next().unwrap_or("");
+            let rejected = params.any(|param| {
+                param.strip_prefix("q=").is_some_and(|q| q.parse::<f32>().ok() == Some(0.0))
+            });
+            name.eq_ignore_ascii_case("gzip") && !rejected
+        });
+}
+
+fn etag(body: &[u8], suffix: &str) -> String {
+    return format!("\"{:016x}{}\"", content::hash(body), suffix);
+}
+
+fn status(status: StatusCode) -> Response {
+    let mut response = Response::new(Body::empty());
+    *response.status_mut() = status;
+    return response;
+}
+
+fn server_error(error: Error) -> Response {
+    let mut response = status(StatusCode::INTERNAL_SERVER_ERROR);
+    response.extensions_mut().insert(ServeError(Arc::new(error)));
+    return response;
+}
+
+async fn serve(State(service): State<Arc<SiteMapService>>, Path(file): Path<String>, headers: HeaderMap) -> Response {
+    let (name, gzip_file) = match file.strip_suffix(".gz") {
+        Some(name) => (name, true),
+        None => (file.as_str(), false),
+    };
+    let generated = {
+        let name = name.to_string();
+        tokio::task::spawn_blocking(move || service.document(&name)).await
+    };
+    let document = match generated {
+        Ok(Some(Ok(document))) => document,
+        Ok(Some(Err(error))) => return server_error(error),
+        Ok(None) => return status(StatusCode::NOT_FOUND),
+        Err(_) => return status(StatusCode::INTERNAL_SERVER_ERROR),
+    };
+    let gzip_encoding = !gzip_file && accepts_gzip(&headers);
+    let (content_type, suffix) = match (gzip_file, gzip_encoding) {
+        (true, _) => ("application/gzip", "-gz"),
+        (false, true) => ("application/xml; charset=utf-8", "-gzip"),
+        (false, false) => ("application/xml; charset=utf-8", ""),
+    };
+    let tag = etag(&document.body, suffix);
+    let mut response_headers = HeaderMap::new();
+    response_headers.insert(header::ETAG, HeaderValue::from_str(&tag).unwrap());
+    if let Some(lastmod) = document.lastmod {
+        let lastmod = lastmod.with_timezone(&Utc).format("%a, %d %b %Y %H:%M:%S GMT").to_string();
+        response_headers.insert(header::LAST_MODIFIED, HeaderValue::from_str(&lastmod).unwrap());
+    }
+    if !gzip_file {
+        response_headers.insert(header::VARY, HeaderValue::from_static("accept-encoding"));
+    }
+    let not_modified = headers.get_all(header::IF_NONE_MATCH).iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .map(|candidate| candidate.trim())
+        .any(|candidate| candidate.strip_prefix("W/").unwrap_or(candidate) == tag || candidate == "*");
+    if not_modified {
+        let mut response = status(StatusCode::NOT_MODIFIED);
+        *response.headers_mut() = response_headers;
+        return response;
+    }
+    let body = if gzip_file || gzip_encoding {
+        match content::gzip(&document.body) {
+            Ok(body) => body,
+            Err(error) => return server_error(error),
+        }
+    } else {
+        document.body
+    };
+    response_headers.insert(header::CONTENT_TYPE, HeaderValue::from_static(content_type));
+    if gzip_encoding {
+        response_headers.insert(header::CONTENT_ENCODING, HeaderValue::from_static("gzip"));
+    }
+    let mut response = Response::new(Body::from(body));
+    *response.headers_mut() = response_headers;
+    return response;
+}
@@ -1,19 +1,23 @@
 //! Contains structures for working with sitemap.
 use crate::Error;
 use url::Url;
-use url;
 use std::convert::From;
-use std::convert::Into;
-use chrono_utils;
 use chrono::DateTime;
 use chrono::FixedOffset;
+use chrono::Duration;
 use chrono_utils::parser::parse_w3c_datetime;
+use chrono_utils::parser::error::ParseError as DateTimeParseError;
 use std::fmt;
-use std::num;
+use std::str::FromStr;
+use std::cmp::Ordering;
+use std::hash::{Hash, Hasher};
 
 /// Url entry. Contains url location, modification time,
 /// priority, update frequency.
-#[derive(Clone,Debug)]
+///
+/// Entries are ordered by location, then by modification time,
+/// update frequency and priority.
+#[derive(Clone,Debug,PartialEq,Eq,PartialOrd,Ord,Hash)]
 pub struct UrlEntry {
     /// URL of the page.
     pub loc: Location,
@@ -38,7 +42,16 @@ impl UrlEntry {
 
     /// Creates builder for `UrlEntry` structure
     pub fn builder() -> UrlEntryBuilder {
-        UrlEntryBuilder { url_entry: UrlEntry::new() }
+        UrlEntryBuilder {
+            url_entry: UrlEntry::new(),
+            loc: String::new(),
+        }
+    }
+}
+
+impl Default for UrlEntry {
+    fn default() -> UrlEntry {
+        return UrlEntry::new();
     }
 }
 
@@ -46,13 +59,14 @@ impl UrlEntry {
 #[derive(Clone,Debug)]
 pub struct UrlEntryBuilder {
     url_entry: UrlEntry,
+    loc: String,
 }
 
 impl UrlEntryBuilder {
     /// Defines `loc` tag
     pub fn loc<S: Into<String>>(mut self, url: S) -> UrlEntryBuilder {
-        let url = url.into();
-        self.url_entry.loc = Location::from(url);
+        self.loc = url.into();
+        self.url_entry.loc = Location::from(self.loc.clone());
         return self;
     }
 
@@ -68,39 +82,52 @@ impl UrlEntryBuilder {
         return self;
     }
 
-    /// Defines `priority` tag
-    pub fn priority(mut self, val: f32) -> UrlEntryBuilder {
-        self.url_entry.priority = Priority::Value(val);
+    /// Defines `priority` tag.
+    /// Priority is rounded half up to hundredths.
+    pub fn priority<P: Into<Priority>>(mut self, priority: P) -> UrlEntryBuilder {
+        self.url_entry.priority = priority.into();
         return self;
     }
 
     /// Builds `UrlEntry` structure
     pub fn build(self) -> Result<UrlEntry, Error> {
-        // TODO: add check for at least the name.
-        if !self.url_entry.loc.is_url() {
-            return Err(Error::Invalid("Required a location in the Url".to_string()));
+        match self.url_entry.loc {
+            Location::Url(_) => {}
+            Location::None => {
+                return Err(Error::MissingLocation);
+            }
+            Location::ParseErr(error) => {
+                return Err(Error::InvalidUrl { value: self.loc, error });
+            }
+        }
+        if let ChangeFreq::ParseErr(ref error) = self.url_entry.changefreq {
+            return Err(Error::InvalidChangeFreq { value: error.description.clone() });
         }
-        if let Priority::Value(val) = self.url_entry.priority {
-            if val > 1.0 || val < 0.0 {
-                return Err(Error::Invalid("priority should be betwheen 0 and 1".to_string()))
+        match self.url_entry.priority {
+            Priority::None | Priority::Value(_) => {}
+            Priority::ParseErr(error) => {
+                return Err(Error::InvalidPriority { value: error.description });
+            }
+            Priority::ErrValueLesserZero(value) | Priority::ErrValueGreaterOne(value) => {
+                return Err(Error::InvalidPriority { value });
             }
         }
         return Ok(self.url_entry);
     }
 }
 
-impl Into<UrlEntry> for UrlEntryBuilder {
+impl From<UrlEntryBuilder> for UrlEntry {
     /// Panics when builder is misconfigured.
-    fn into(self) -> UrlEntry {
-        return self.build().unwrap();
+    fn from(builder: UrlEntryBuilder) -> UrlEntry {
+        return builder.build().unwrap();
     }
 }
 
-impl Into<UrlEntry> for Url {
+impl From<Url> for UrlEntry {
     /// Notably does not panic
-    fn into( self ) -> UrlEntry {
+    fn from(url: Url) -> UrlEntry {
         UrlEntry {
-            loc: Location::from(self),
+            loc: Location::from(url),
             lastmod: LastMod::None,
             changefreq: ChangeFreq::None,
             priority: Priority::None,
@@ -108,10 +135,10 @@ impl Into<UrlEntry> for Url {
     }
 }
 
-impl Into<UrlEntry> for String {
+impl From<String> for UrlEntry {
     /// Panics when url is invalid
-    fn into(self) -> UrlEntry {
-        let location = Location::from(self);
+    fn from(url: String) -> UrlEntry {
+        let location = Location::from(url);
         if let Location::ParseErr(error) = location {
             panic!("Unable to parse location: {}", error);
         }
@@ -124,16 +151,17 @@ impl Into<UrlEntry> for String {
     }
 }
 
-impl Into<UrlEntry> for &'static str {
+impl From<&'static str> for UrlEntry {
     /// Panics when url is invalid
-    fn into(self) -> UrlEntry {
-        let location: String = self.into();
-        return location.into();
+    fn from(url: &'static str) -> UrlEntry {
+        return UrlEntry::from(url.to_string());
     }
 }
 
 /// Sitemap entry. Contains url location and modification time.
-#[derive(Clone,Debug)]
+///
+/// Entries are ordered by location, then by modification time.
+#[derive(Clone,Debug,PartialEq,Eq,PartialOrd,Ord,Hash)]
 pub struct SiteMapEntry {
     /// URL of the sitemap.
     pub loc: Location,
@@ -151,22 +179,31 @@ impl SiteMapEntry {
 
     /// Creates builder for `SiteMapEntry` structure
     pub fn builder() -> SiteMapEntryBuilder {
-        SiteMapEntryBuilder { sitemap_entry: SiteMapEntry::new() }
+        SiteMapEntryBuilder {
+            sitemap_entry: SiteMapEntry::new(),
+            loc: String::new(),
+        }
     }
 }
 
+impl Default for SiteMapEntry {
+    fn default() -> SiteMapEntry {
+        return SiteMapEntry::new();
+    }
+}
 
 /// Builds `SiteMapEntry` structure
 #[derive(Debug,Clone)]
 pub struct SiteMapEntryBuilder {
     sitemap_entry: SiteMapEntry,
+    loc: String,
 }
 
 impl SiteMapEntryBuilder {
     /// Defines `loc` tag
     pub fn loc<S: Into<String>>(mut self, url: S) -> SiteMapEntryBuilder {
-        let url = url.into();
-        self.sitemap_entry.loc = Location::from(url);
+        self.loc = url.into();
+        self.sitemap_entry.loc = Location::from(self.loc.clone());
         return self;
     }
 
@@ -178,36 +215,35 @@ impl SiteMapEntryBuilder {
 
     /// Builds `SiteMapEntry` structure
     pub fn build(self) -> Result<SiteMapEntry, Error> {
-        // TODO: add check for at least the name.
-        if let Location::Url(_) = self.sitemap_entry.loc {
-            Ok(self.sitemap_entry)
-        } else {
-            Err(Error::Invalid("Required a location in the sitemap".to_string()))
+        match self.sitemap_entry.loc {
+            Location::Url(_) => Ok(self.sitemap_entry),
+            Location::None => Err(Error::MissingLocation),
+            Location::ParseErr(error) => Err(Error::InvalidUrl { value: self.loc, error }),
         }
     }
 }
 
-impl Into<SiteMapEntry> for SiteMapEntryBuilder {
+impl From<SiteMapEntryBuilder> for SiteMapEntry {
     /// Panics when builder is misconfigured.
-    fn into(self) -> SiteMapEntry {
-        return self.build().unwrap();
+    fn from(builder: SiteMapEntryBuilder) -> SiteMapEntry {
+        return builder.build().unwrap();
     }
 }
 
-impl Into<SiteMapEntry> for Url {
+impl From<Url> for SiteMapEntry {
     /// Notably does not panic
-    fn into( self ) -> SiteMapEntry {
+    fn from(url: Url) -> SiteMapEntry {
         SiteMapEntry {
-            loc: Location::from( self ),
+            loc: Location::from(url),
             lastmod: LastMod::None,
         }
     }
 }
 
-impl Into<SiteMapEntry> for String {
+impl From<String> for SiteMapEntry {
     /// Panics when url is invalid
-    fn into(self) -> SiteMapEntry {
-        let location = Location::from(self);
+    fn from(url: String) -> SiteMapEntry {
+        let location = Location::from(url);
         if let Location::ParseErr(error) = location {
             panic!("Unable to parse location: {}", error);
         }
@@ -218,15 +254,16 @@ impl Into<SiteMapEntry> for String {
     }
 }
 
-impl Into<SiteMapEntry> for &'static str {
+impl From<&'static str> for SiteMapEntry {
     /// Panics when url is invalid
-    fn into(self) -> SiteMapEntry {
-        let location: String = self.into();
-        return location.into();
+    fn from(url: &'static str) -> SiteMapEntry {
+        return SiteMapEntry::from(url.to_string());
     }
 }
 
 /// Url location.
+///
+/// Locations are ordered as `None`, urls, parse errors.
 #[derive(Debug,Clone)]
 pub enum Location {
     /// No value.
@@ -251,28 +288,89 @@ impl Location {
 
     /// Checks is location equals url
     pub fn is_url(&self) -> bool {
-        return match *self {
-            Location::Url(_) => true,
-            _ => false,
-        }
+        return matches!(*self, Location::Url(_));
     }
 
     /// Checks is location equals none
     pub fn is_none(&self) -> bool {
-        return match *self {
-            Location::None => true,
-            _ => false,
-        }
+        return matches!(*self, Location::None);
     }
 
     /// Checks is location contains parse error.
     pub fn is_parse_error(&self) -> bool {
-        return match *self {
-            Location::ParseErr(_) => true,
+        return matches!(*self, Location::ParseErr(_));
+    }
+
+    fn rank(&self) -> u8 {
+        match *self {
+            Location::None => 0,
+            Location::Url(_) => 1,
+            Location::ParseErr(_) => 2,
+        }
+    }
+}
+impl PartialEq for Location {
+    fn eq(&self, other: &Location) -> bool {
+        match (self, other) {
+            (Location::None, Location::None) => true,
+            (Location::Url(url), Location::Url(other_url)) => url == other_url,
+            (Location::ParseErr(error), Location::ParseErr(other_error)) => error == other_error,
             _ => false,
         }
     }
 }
+impl Eq for Location {}
+impl PartialOrd for Location {
+    fn partial_cmp(&self, other: &Location) -> Option<Ordering> {
+        return Some(self.cmp(other));
+    }
+}
+impl Ord for Location {
+    fn cmp(&self, other: &Location) -> Ordering {
+        match (self, other) {
+            (Location::Url(url), Location::Url(other_url)) => url.cmp(other_url),
+            (Location::ParseErr(error), Location::ParseErr(other_error)) => {
+                // `url::ParseError` has no ordering, its descriptions are unique.
+                error.to_string().cmp(&other_error.to_string())
+            }
+            _ => self.rank().cmp(&other.rank()),
+        }
+    }
+}
+impl Hash for Location {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.rank().hash(state);
+        match *self {
+            Location::None => {}
+            Location::Url(ref url) => url.hash(state),
+            Location::ParseErr(ref error) => error.to_string().hash(state),
+        }
+    }
+}
+impl fmt::Display for Location {
+    /// Writes url, nothing for `None` and the error description for parse errors.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Location::None => Ok(()),
+            Location::Url(ref url) => f.write_str(url.as_str()),
+            Location::ParseErr(ref error) => write!(f, "{}", error),
+        }
+    }
+}
+impl FromStr for Location {
+    type Err = Error;
+    /// Parses url, rejecting invalid values with `Error::InvalidUrl`.
+    fn from_str(url: &str) -> Result<Location, Error> {
+        match Url::parse(url) {
+            Ok(url) => {
+                return Ok(Location::Url(url));
+            }
+            Err(error) => {
+                return Err(Error::InvalidUrl { value: url.to_string(), error });
+            }
+        }
+    }
+}
 impl From<Url> for Location {
     ///Wraps a Url into a Location enum
     fn from( url: Url ) -> Self {
@@ -294,6 +392,9 @@ impl From<String> for Location {
     }
 }
 /// The date of last modification of the resource.
+///
+/// Modification times are compared as instants, regardless of their offsets.
+/// Values are ordered as `None`, modification times, parse errors.
 #[derive(Debug,Clone)]
 pub enum LastMod {
     /// No value.
@@ -301,20 +402,93 @@ pub enum LastMod {
     /// Modification time
     DateTime(DateTime<FixedOffset>),
     /// Parse error
-    ParseErr(chrono_utils::parser::error::ParseError),
+    ParseErr(DateTimeParseError),
 }
 impl LastMod {
     /// Returns modification time if present.
     pub fn get_time(&self) -> Option<DateTime<FixedOffset>> {
         match *self {
             LastMod::DateTime(ref time) => {
-                return Some(time.clone());
+                return Some(*time);
             }
             _ => {
                 return None;
             }
         }
     }
+
+    fn rank(&self) -> u8 {
+        match *self {
+            LastMod::None => 0,
+            LastMod::DateTime(_) => 1,
+            LastMod::ParseErr(_) => 2,
+        }
+    }
+}
+fn datetime_error_key(error: &DateTimeParseError) -> (u8, usize, usize) {
+    return (error.error_kind as u8, error.position_begin, error.position_end);
+}
+impl PartialEq for LastMod {
+    fn eq(&self, other: &LastMod) -> bool {
+        match (self, other) {
+            (LastMod::None, LastMod::None) => true,
+            (LastMod::DateTime(time), LastMod::DateTime(other_time)) => time == other_time,
+            (LastMod::ParseErr(error), LastMod::ParseErr(other_error)) => error == other_error,
+            _ => false,
+        }
+    }
+}
+impl Eq for LastMod {}
+impl PartialOrd for LastMod {
+    fn partial_cmp(&self, other: &LastMod) -> Option<Ordering> {
+        return Some(self.cmp(other));
+    }
+}
+impl Ord for LastMod {
+    fn cmp(&self, other: &LastMod) -> Ordering {
+        match (self, other) {
+            (LastMod::DateTime(time), LastMod::DateTime(other_time)) => time.cmp(other_time),
+            (LastMod::ParseErr(error), LastMod::ParseErr(other_error)) => {
+                datetime_error_key(error).cmp(&datetime_error_key(other_error))
+            }
+            _ => self.rank().cmp(&other.rank()),
+        }
+    }
+}
+impl Hash for LastMod {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.rank().hash(state);
+        match *self {
+            LastMod::None => {}
+            LastMod::DateTime(ref time) => time.hash(state),
+            LastMod::ParseErr(ref error) => datetime_error_key(error).hash(state),
+        }
+    }
+}
+impl fmt::Display for LastMod {
+    /// Writes modification time in RFC 3339 format, nothing for `None`
+    /// and the error description for parse errors.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            LastMod::None => Ok(()),
+            LastMod::DateTime(ref time) => f.write_str(&time.to_rfc3339()),
+            LastMod::ParseErr(ref error) => write!(f, "{}", error),
+        }
+    }
+}
+impl FromStr for LastMod {
+    type Err = Error;
+    /// Parses W3C datetime, rejecting invalid values with `Error::InvalidLastMod`.
+    fn from_str(time: &str) -> Result<LastMod, Error> {
+        match parse_w3c_datetime(time) {
+            Ok(time) => {
+                return Ok(LastMod::DateTime(time));
+            }
+            Err(error) => {
+                return Err(Error::InvalidLastMod { value: time.to_string(), error });
+            }
+        }
+    }
 }
 impl From<String> for LastMod {
     fn from(time: String) -> Self {
@@ -329,7 +503,7 @@ impl From<String> for LastMod {
     }
 }
 /// Error parsing URL Priority.
-#[derive(PartialEq,Debug,Clone)]
+#[derive(PartialEq,Eq,PartialOrd,Ord,Hash,Debug,Clone)]
 pub struct ChangeFreqParseError {
     /// Error description
     pub description: String,
@@ -337,7 +511,7 @@ pub struct ChangeFreqParseError {
 impl ChangeFreqParseError {
     /// Creates new error.
     pub fn new(description: String) -> ChangeFreqParseError {
-        ChangeFreqParseError { description: description }
+        ChangeFreqParseError { description }
     }
 }
 impl fmt::Display for ChangeFreqParseError {
@@ -346,7 +520,10 @@ impl fmt::Display for ChangeFreqParseError {
     }
 }
 /// How frequently the page is likely to change.
-#[derive(PartialEq,Debug,Clone)]
+///
+/// Values are ordered as `None`, then from the most frequent to the least frequent
+/// change, then parse errors.
+#[derive(PartialEq,Eq,PartialOrd,Ord,Hash,Debug,Clone)]
 pub enum ChangeFreq {
     /// No value.
     None,
@@ -368,19 +545,73 @@ pub enum ChangeFreq {
     ParseErr(ChangeFreqParseError),
 }
 impl ChangeFreq {
+    /// Every protocol value, from the most frequent to the least frequent change.
+    pub const ALL: [ChangeFreq; 7] = [
+        ChangeFreq::Always,
+        ChangeFreq::Hourly,
+        ChangeFreq::Daily,
+        ChangeFreq::Weekly,
+        ChangeFreq::Monthly,
+        ChangeFreq::Yearly,
+        ChangeFreq::Never,
+    ];
+
+    /// Returns protocol value, empty string for `None` and parse errors.
+    #[deprecated(since = "0.5.0", note = "use `Display` and `FromStr` instead")]
     pub fn as_str(&self) -> &str {
+        return self.name().unwrap_or("");
+    }
+
+    /// Returns approximate interval between changes.
+    /// Months are counted as 30 days and years as 365 days.
+    /// Returns `None` for `Never`, `None` and parse errors.
+    pub fn interval(&self) -> Option<Duration> {
         match *self {
-            ChangeFreq::None => "",
-            ChangeFreq::Always => "always",
-            ChangeFreq::Hourly => "hourly",
-            ChangeFreq::Daily => "daily",
-            ChangeFreq::Weekly => "weekly",
-            ChangeFreq::Monthly => "monthly",
-            ChangeFreq::Yearly => "yearly",
-            ChangeFreq::Never => "never",
-            ChangeFreq::ParseErr(_) => "",
+            ChangeFreq::Always => Some(Duration::zero()),
+            ChangeFreq::Hourly => Some(Duration::hours(1)),
+            ChangeFreq::Daily => Some(Duration::days(1)),
+            ChangeFreq::Weekly => Some(Duration::weeks(1)),
+            ChangeFreq::Monthly => Some(Duration::days(30)),
+            ChangeFreq::Yearly => Some(Duration::days(365)),
+            ChangeFreq::Never | ChangeFreq::None | ChangeFreq::ParseErr(_) => None,
         }
+    }
 
+    fn name(&self) -> Option<&'static str> {
+        match *self {
+            ChangeFreq::None => None,
+            ChangeFreq::Always => Some("always"),
+            ChangeFreq::Hourly => Some("hourly"),
+            ChangeFreq::Daily => Some("daily"),
+            ChangeFreq::Weekly => Some("weekly"),
+            ChangeFreq::Monthly => Some("monthly"),
+            ChangeFreq::Yearly => Some("yearly"),
+            ChangeFreq::Never => Some("never"),
+            ChangeFreq::ParseErr(_) => None,
+        }
+    }
+}
+impl fmt::Display for ChangeFreq {
+    /// Writes protocol value, nothing for `None` and the error description for parse errors.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ChangeFreq::ParseErr(ref error) => write!(f, "{}", error),
+            _ => f.write_str(self.name().unwrap_or("")),
+        }
+    }
+}
+impl FromStr for ChangeFreq {
+    type Err = Error;
+    /// Parses protocol value ignoring case, rejecting others with `Error::InvalidChangeFreq`.
+    fn from_str(changefreq: &str) -> Result<ChangeFreq, Error> {
+        match ChangeFreq::from(changefreq.to_string()) {
+            ChangeFreq::ParseErr(_) | ChangeFreq::None => {
+                return Err(Error::InvalidChangeFreq { value: changefreq.to_string() });
+            }
+            changefreq => {
+                return Ok(changefreq);
+            }
+        }
     }
 }
 impl From<String> for ChangeFreq {
@@ -415,23 +646,125 @@ impl From<String> for ChangeFreq {
     }
 }
 
+/// Error parsing URL Priority.
+#[derive(PartialEq,Eq,PartialOrd,Ord,Hash,Debug,Clone)]
+pub struct PriorityParseError {
+    /// Error description
+    pub description: String,
+}
+impl PriorityParseError {
+    /// Creates new error.
+    pub fn new(description: String) -> PriorityParseError {
+        PriorityParseError { description }
+    }
+}
+impl fmt::Display for PriorityParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Not a decimal number '{}'", self.description)
+    }
+}
+
+/// Priority value stored as a fixed-point number of hundredths.
+///
+/// Values are displayed in canonical form with one decimal digit, or two
+/// when hundredths are significant: `0.0`, `0.5`, `0.25`, `1.0`.
+#[derive(PartialEq,Eq,PartialOrd,Ord,Hash,Debug,Clone,Copy)]
+pub struct PriorityValue(u8);
+impl PriorityValue {
+    /// Lowest priority, `0.0`.
+    pub const MIN: PriorityValue = PriorityValue(0);
+    /// Default priority of the protocol, `0.5`.
+    pub const DEFAULT: PriorityValue = PriorityValue(50);
+    /// Highest priority, `1.0`.
+    pub const MAX: PriorityValue = PriorityValue(100);
+
+    /// Creates priority from hundredths, returns `None` when greater than 100.
+    pub fn from_hundredths(hundredths: u8) -> Option<PriorityValue> {
+        if hundredths > 100 {
+            return None;
+        }
+        return Some(PriorityValue(hundredths));
+    }
+
+    /// Creates priority from tenths, returns `None` when greater than 10.
+    pub fn from_tenths(tenths: u8) -> Option<PriorityValue> {
+        if tenths > 10 {
+            return None;
+        }
+        return Some(PriorityValue(tenths * 10));
+    }
+
+    /// Returns priority in hundredths.
+    pub fn hundredths(&self) -> u8 {
+        return self.0;
+    }
+
+    /// Returns priority as floating point number.
+    pub fn as_f32(&self) -> f32 {
+        return f32::from(self.0) / 100.0;
+    }
+}
+impl fmt::Display for PriorityValue {
+    // `is_multiple_of` requires Rust 1.87.
+    #[allow(clippy::manual_is_multiple_of)]
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.0 % 10 == 0 {
+            write!(f, "{}.{}", self.0 / 100, self.0 % 100 / 10)
+        } else {
+            write!(f, "{}.{:02}", self.0 / 100, self.0 % 100)
+        }
+    }
+}
+
+impl FromStr for PriorityValue {
+    type Err = Error;
+    /// Parses priority, rejecting invalid values with `Error::InvalidPriority`.
+    fn from_str(priority: &str) -> Result<PriorityValue, Error> {
+        match Priority::from(priority.to_string()) {
+            Priority::Value(value) => {
+                return Ok(value);
+            }
+            _ => {
+                return Err(Error::InvalidPriority { value: priority.to_string() });
+            }
+        }
+    }
+}
+
 /// The priority of this URL relative to other URLs on the site.
-#[derive(Debug,Clone)]
+///
+/// Priority is parsed from decimal text without floating point conversion and
+/// rounded half up to hundredths, so `0.125` becomes `0.13`. Values outside of
+/// `0..=1` are kept as errors with the original text.
+/// Values are ordered as `None`, priorities, then errors.
+#[derive(PartialEq,Eq,PartialOrd,Ord,Hash,Debug,Clone)]
 pub enum Priority {
     /// No value.
     None,
     /// Priority
-    Value(f32),
+    Value(PriorityValue),
     /// Parse error.
-    ParseErr(num::ParseFloatError),
+    ParseErr(PriorityParseError),
     /// Error: priority lesser than zero.
-    ErrValueLesserZero(f32),
+    ErrValueLesserZero(String),
     /// Error: priority greater than one.
-    ErrValueGreaterOne(f32),
+    ErrValueGreaterOne(String),
 }
 impl Priority {
     /// Returns priority if present.
     pub fn get_priority(&self) -> Option<f32> {
+        match *self {
+            Priority::Value(value) => {
+                return Some(value.as_f32());
+            }
+            _ => {
+                return None;
+            }
+        }
+    }
+
+    /// Returns fixed-point priority if present.
+    pub fn get_value(&self) -> Option<PriorityValue> {
         match *self {
             Priority::Value(value) => {
                 return Some(value);
@@ -442,22 +775,76 @@ impl Priority {
         }
     }
 }
+impl fmt::Display for Priority {
+    /// Writes priority in canonical form, nothing for `None` and the error description for errors.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Priority::None => Ok(()),
+            Priority::Value(value) => write!(f, "{}", value),
+            Priority::ParseErr(ref error) => write!(f, "{}", error),
+            Priority::ErrValueLesserZero(ref value) | Priority::ErrValueGreaterOne(ref value) => {
+                write!(f, "Priority '{}' should be between 0 and 1", value)
+            }
+        }
+    }
+}
+impl FromStr for Priority {
+    type Err = Error;
+    /// Parses priority, rejecting invalid values with `Error::InvalidPriority`.
+    fn from_str(priority: &str) -> Result<Priority, Error> {
+        return priority.parse::<PriorityValue>().map(Priority::Value);
+    }
+}
+impl From<PriorityValue> for Priority {
+    fn from(value: PriorityValue) -> Self {
+        Priority::Value(value)
+    }
+}
+impl From<f32> for Priority {
+    /// Rounds the shortest decimal representation of the number half up to hundredths.
+    fn from(priority: f32) -> Self {
+        if !priority.is_finite() {
+            return Priority::ParseErr(PriorityParseError::new(priority.to_string()));
+        }
+        return Priority::from(priority.to_string());
+    }
+}
 impl From<String> for Priority {
     fn from(priority: String) -> Self {
-        let value = priority.parse::<f32>();
-        match value {
-            Ok(value) => {
-                if value > 1.0 {
-                    return Priority::ErrValueGreaterOne(value);
-                } else if value < 0.0 {
-                    return Priority::ErrValueLesserZero(value);
-                } else {
-                    return Priority::Value(value);
-                }
-            }
-            Err(error) => {
-                return Priority::ParseErr(error);
-            }
+        let text = priority.trim();
+        let (negative, text) = match text.as_bytes().first() {
+            Some(b'-') => (true, &text[1..]),
+            Some(b'+') => (false, &text[1..]),
+            _ => (false, text),
+        };
+        let (integer, fraction) = match text.find('.') {
+            Some(index) => (&text[..index], &text[index + 1..]),
+            None => (text, ""),
+        };
+        let is_digits = |part: &str| part.bytes().all(|byte| byte.is_ascii_digit());
+        if integer.is_empty() && fraction.is_empty() || !is_digits(integer) || !is_digits(fraction) {
+            return Priority::ParseErr(PriorityParseError::new(priority));
+        }
+        let integer = integer.trim_start_matches('0');
+        let fraction_is_zero = fraction.bytes().all(|byte| byte == b'0');
+        if negative && !(integer.is_empty() && fraction_is_zero) {
+            return Priority::ErrValueLesserZero(priority);
+        }
+        let greater_one = match integer {
+            "" => false,
+            "1" => !fraction_is_zero,
+            _ => true,
+        };
+        if greater_one {
+            return Priority::ErrValueGreaterOne(priority);
+        }
+        let digit = |index: usize| fraction.as_bytes().get(index).map_or(0, |byte| byte - b'0');
+        let mut hundredths = 10 * digit(0) + digit(1);
+        if integer == "1" {
+            hundredths = 100;
+        } else if digit(2) >= 5 {
+            hundredths += 1;
         }
+        return Priority::Value(PriorityValue(hundredths));
     }
 }
@@ -9,32 +9,432 @@
 //! use sitemap::structs::UrlEntry;
 //! use std::io::stdout;
 //! fn main() {
+//!     let urls = vec![
+//!         UrlEntry::from("http://github.com"),
+//!         UrlEntry::builder().loc("http://google.com").build().unwrap(),
+//!         UrlEntry::builder().loc("http://yandex.ru").build().unwrap(),
+//!     ];
+//!     let (_, summary) = SiteMapWriter::new(stdout())
+//!         .write_urlset_from_iter(urls)
+//!         .expect("Unable to write sitemap");
+//!     assert_eq!(summary.count, 3);
+//! }
+//! ```
+//!
+//! Writing urls one by one.
+//!
+//! ```rust
+//! use sitemap::writer::SiteMapWriter;
+//! use sitemap::structs::UrlEntry;
+//! use std::io::stdout;
+//! fn main() {
 //!     let mut output = stdout();
 //!     let sitemap_writer = SiteMapWriter::new(&mut output);
 //!     let mut urlwriter = sitemap_writer.start_urlset().expect("Unable to write urlset");
 //!     urlwriter.url("http://github.com").expect("Unable to write url");
 //!     urlwriter.url(UrlEntry::builder().loc("http://google.com")).expect("Unable to write url");
-//!     urlwriter.url(UrlEntry::builder().loc("http://yandex.ru").build().unwrap()).expect("Unable to write url");
 //!     urlwriter.end().expect("Unable to write close tags");
 //! }
 //! ```
+//!
+//! Writing sitemap with XSL stylesheet and generator comment.
+//!
+//! ```rust
+//! use sitemap::writer::{SiteMapWriter, SiteMapWriterConfig};
+//! use std::io::stdout;
+//! fn main() {
+//!     let config = SiteMapWriterConfig::new()
+//!         .stylesheet("/sitemap.xsl")
+//!         .comment("generator: my-site 1.0");
+//!     let sitemap_writer = SiteMapWriter::with_config(stdout(), config);
+//!     let mut urlwriter = sitemap_writer.start_urlset().expect("Unable to write urlset");
+//!     urlwriter.url("http://github.com").expect("Unable to write url");
+//!     urlwriter.end().expect("Unable to write close tags");
+//! }
+//! ```
+use std::io;
 use std::io::Write;
-use xml::writer::{EventWriter, EmitterConfig, XmlEvent};
-use crate::Error;
+use std::fmt;
+use std::marker::PhantomData;
+use crate::backend::{XmlWriter, escape_attribute, escape_pcdata};
+use crate::encoding::is_utf8_label;
+use crate::{Error, Limit};
 use crate::structs::{UrlEntry, Location, LastMod, ChangeFreq, Priority, SiteMapEntry};
+use crate::scope::SiteMapScope;
+use url::{Host, Position, Url};
+#[cfg(feature = "output")]
+use crate::output::AtomicFile;
 
 const DEFAULT_NAMESPACE: &str = "http://www.sitemaps.org/schemas/sitemap/0.9";
 
+/// Maximum number of urls in sitemap or sitemaps in sitemap index.
+pub const MAX_ENTRIES: usize = 50_000;
+/// Maximum size of uncompressed sitemap or sitemap index in bytes.
+pub const MAX_BYTES: usize = 50 * 1024 * 1024;
+
+/// Counts bytes passed to the inner writer.
+struct CountingWrite<T: Write + Sized> {
+    inner: T,
+    bytes: usize,
+}
+
+impl<T: Write + Sized> Write for CountingWrite<T> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.inner.write(buf)?;
+        self.bytes += written;
+        return Ok(written);
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        return self.inner.flush();
+    }
+}
+
+/// Version of the XML declaration.
+#[derive(Clone,Copy,Debug,PartialEq,Eq)]
+pub enum XmlVersion {
+    /// XML 1.0
+    Version10,
+    /// XML 1.1
+    Version11,
+}
+
+/// Form of written urls.
+///
+/// Urls are stored as parsed by `url` crate: non-ASCII characters are
+/// percent-encoded and international domain names are converted into punycode.
+#[derive(Clone,Copy,Debug,PartialEq,Eq)]
+pub enum IriPolicy {
+    /// Writes urls percent-encoded as RFC 3986 requires, e.g. `http://xn--e1afmkfd.xn--p1ai/%D0%BF%D1%83%D1%82%D1%8C`.
+    PercentEncode,
+    /// Writes urls as RFC 3987 IRIs, e.g. `http://пример.рф/путь`.
+    /// Percent-encoded ASCII characters stay encoded.
+    Unicode,
+}
+
+/// Sitemap writer configuration.
+///
+/// Default configuration writes `<?xml version="1.0" encoding="UTF-8"?>` declaration
+/// and indents elements with two spaces.
+#[derive(Clone,Debug)]
+pub struct SiteMapWriterConfig {
+    stylesheets: Vec<(String, String)>,
+    comments: Vec<String>,
+    indent: Option<String>,
+    line_separator: String,
+    declaration: bool,
+    version: XmlVersion,
+    encoding: String,
+    standalone: Option<bool>,
+    close_on_drop: bool,
+    max_entries: usize,
+    max_bytes: usize,
+    iri_policy: IriPolicy,
+}
+
+impl Default for SiteMapWriterConfig {
+    fn default() -> SiteMapWriterConfig {
+        SiteMapWriterConfig {
+            stylesheets: Vec::new(),
+            comments: Vec::new(),
+            indent: Some("  ".to_string()),
+            line_separator: "\n".to_string(),
+            declaration: true,
+            version: XmlVersion::Version10,
+            encoding: "UTF-8".to_string(),
+            standalone: None,
+            close_on_drop: false,
+            max_entries: MAX_ENTRIES,
+            max_bytes: MAX_BYTES,
+            iri_policy: IriPolicy::PercentEncode,
+        }
+    }
+}
+
+impl SiteMapWriterConfig {
+    /// Creates default configuration.
+    pub fn new() -> SiteMapWriterConfig {
+        return SiteMapWriterConfig::default();
+    }
+
+    /// Puts each element on its own line, indented by `indent` per nesting level.
+    pub fn indent<S: Into<String>>(mut self, indent: S) -> SiteMapWriterConfig {
+        self.indent = Some(indent.into());
+        return self;
+    }
+
+    /// Writes document without line breaks and indentation.
+    pub fn compact(mut self) -> SiteMapWriterConfig {
+        self.indent = None;
+        return self;
+    }
+
+    /// Defines line separator used with indentation, `\n` by default.
+    pub fn line_separator<S: Into<String>>(mut self, line_separator: S) -> SiteMapWriterConfig {
+        self.line_separator = line_separator.into();
+        return self;
+    }
+
+    /// Enables or disables writing of the XML declaration.
+    pub fn xml_declaration(mut self, enabled: bool) -> SiteMapWriterConfig {
+        self.declaration = enabled;
+        return self;
+    }
+
+    /// Defines version of the XML declaration.
+    pub fn xml_version(mut self, version: XmlVersion) -> SiteMapWriterConfig {
+        self.version = version;
+        return self;
+    }
+
+    /// Defines encoding of the XML declaration.
+    /// Documents are always written in UTF-8 as the protocol requires,
+    /// so starting a document with another encoding returns `Error::UnsupportedEncoding`.
+    pub fn encoding<S: Into<String>>(mut self, encoding: S) -> SiteMapWriterConfig {
+        self.encoding = encoding.into();
+        return self;
+    }
+
+    /// Defines `standalone` attribute of the XML declaration.
+    pub fn standalone(mut self, standalone: bool) -> SiteMapWriterConfig {
+        self.standalone = Some(standalone);
+        return self;
+    }
+
+    /// Closes the root element when `UrlSetWriter` or `SiteMapIndexWriter` is dropped
+    /// without `end()`, e.g. after an early return. Write errors on drop are ignored.
+    ///
+    /// Otherwise dropping an unfinished writer is reported as a `log` warning.
+    pub fn close_on_drop(mut self, enabled: bool) -> SiteMapWriterConfig {
+        self.close_on_drop = enabled;
+        return self;
+    }
+
+    /// Defines form of written urls, percent-encoded by default.
+    pub fn iri_policy(mut self, iri_policy: IriPolicy) -> SiteMapWriterConfig {
+        self.iri_policy = iri_policy;
+        return self;
+    }
+
+    /// Lowers the limit of entries in a document, at most `MAX_ENTRIES`.
+    pub fn max_entries(mut self, max_entries: usize) -> SiteMapWriterConfig {
+        self.max_entries = max_entries.min(MAX_ENTRIES);
+        return self;
+    }
+
+    /// Lowers the limit of document size in bytes, at most `MAX_BYTES`.
+    pub fn max_bytes(mut self, max_bytes: usize) -> SiteMapWriterConfig {
+        self.max_bytes = max_bytes.min(MAX_BYTES);
+        return self;
+    }
+
+    /// Adds `<?xml-stylesheet type="text/xsl" href="..."?>` processing instruction
+    /// before the root element.
+    pub fn stylesheet<S: Into<String>>(self, href: S) -> SiteMapWriterConfig {
+        return self.stylesheet_with_type("text/xsl", href);
+    }
+
+    /// Adds `<?xml-stylesheet?>` processing instruction with specified mime type
+    /// before the root element.
+    pub fn stylesheet_with_type<M: Into<String>, S: Into<String>>(
+        mut self,
+        mime_type: M,
+        href: S,
+    ) -> SiteMapWriterConfig {
+        self.stylesheets.push((mime_type.into(), href.into()));
+        return self;
+    }
+
+    /// Adds comment before the root element.
+    /// Double hyphens, forbidden inside comments, are separated by a space.
+    pub fn comment<S: Into<String>>(mut self, comment: S) -> SiteMapWriterConfig {
+        let mut comment = comment.into();
+        while comment.contains("--") {
+            comment = comment.replace("--", "- -");
+        }
+        if comment.ends_with('-') {
+            comment.push(' ');
+        }
+        self.comments.push(comment);
+        return self;
+    }
+
+    /// Returns text of the location according to the IRI policy.
+    fn location(&self, loc: &Url) -> String {
+        match self.iri_policy {
+            IriPolicy::PercentEncode => loc.to_string(),
+            IriPolicy::Unicode => iri(loc),
+        }
+    }
+
+    /// Returns size of the line break before tag at `level`.
+    fn newline_len(&self, level: usize) -> usize {
+        match self.indent {
+            Some(ref indent) => self.line_separator.len() + level * indent.len(),
+            None => 0,
+        }
+    }
+
+    /// Returns the upper bound of the entry size in bytes.
+    fn entry_len(&self, ele: &str, fields: &[(&str, String)]) -> usize {
+        // Closing bracket of the previous tag may still be pending in the emitter.
+        let mut len = 1;
+        len += self.newline_len(1) + ele.len() + 2;
+        for (name, content) in fields {
+            len += self.newline_len(2) + 2 * name.len() + 5 + escape_pcdata(content).len();
+        }
+        len += self.newline_len(1) + ele.len() + 3;
+        return len;
+    }
+}
+
+/// Converts url into IRI, decoding the host and percent-encoded UTF-8 characters.
+fn iri(url: &Url) -> String {
+    let mut iri = url[..Position::BeforeHost].to_string();
+    match url.host() {
+        Some(Host::Domain(domain)) => {
+            let (domain, result) = idna::domain_to_unicode(domain);
+            if result.is_ok() {
+                iri.push_str(&domain);
+            } else {
+                iri.push_str(&url[Position::BeforeHost..Position::AfterHost]);
+            }
+        }
+        _ => iri.push_str(&url[Position::BeforeHost..Position::AfterHost]),
+    }
+    // Serialized url consists of ASCII characters only.
+    let rest = &url[Position::AfterHost..];
+    let bytes = rest.as_bytes();
+    let mut pos = 0;
+    while pos < bytes.len() {
+        // Run of percent-encoded non-ASCII bytes.
+        let mut end = pos;
+        let mut decoded = Vec::new();
+        while end + 3 <= bytes.len() && bytes[end] == b'%' {
+            match u8::from_str_radix(&rest[end + 1..end + 3], 16) {
+                Ok(byte) if byte >= 0x80 => decoded.push(byte),
+                _ => break,
+            }
+            end += 3;
+        }
+        if end == pos {
+            iri.push(bytes[pos] as char);
+            pos += 1;
+            continue;
+        }
+        match String::from_utf8(decoded) {
+            Ok(text) if text.chars().all(is_iri_char) => iri.push_str(&text),
+            _ => iri.push_str(&rest[pos..end]),
+        }
+        pos = end;
+    }
+    return iri;
+}
+
+/// Returns true for characters allowed unencoded in IRI.
+fn is_iri_char(c: char) -> bool {
+    // Bidirectional formatting characters should stay encoded, RFC 3987 section 4.1.
+    let bidi = matches!(c, '\u{200E}' | '\u{200F}' | '\u{202A}'..='\u{202E}');
+    return !c.is_control() && !c.is_whitespace() && !bidi;
+}
+
+/// Summary of a complete document.
+#[derive(Clone,Copy,Debug,PartialEq,Eq)]
+pub struct WriteSummary {
+    /// Number of written entries.
+    pub count: usize,
+    /// Size of the document in bytes.
+    pub bytes: usize,
+}
+
+/// State of a writer which has not written the root element yet.
+#[derive(Debug)]
+pub enum Empty {}
+
+/// State of a writer which has written a complete document.
+///
+/// Only the underlying stream is accessible in this state, so a second root
+/// element can not be written into the same document.
+#[derive(Debug)]
+pub enum Ended {}
+
 /// Writes xml tags into writer.
-pub struct SiteMapWriter<T: Write + Sized> {
-    writer: EventWriter<T>,
+///
+/// The writer produces exactly one document. `UrlSetWriter::end()` and
+/// `SiteMapIndexWriter::end()` return the writer in `Ended` state,
+/// which does not allow starting another root element.
+/// Use `SiteMapSequence` to write several documents.
+pub struct SiteMapWriter<T: Write + Sized, S = Empty> {
+    writer: XmlWriter<CountingWrite<T>>,
+    config: SiteMapWriterConfig,
+    scope: Option<SiteMapScope>,
+    state: PhantomData<S>,
+}
+
+impl<T: Write + Sized, S> SiteMapWriter<T, S> {
+    /// Returns reference to the underlying stream.
+    pub fn get_ref(&self) -> &T {
+        return &self.writer.inner_ref().inner;
+    }
+
+    /// Returns mutable reference to the underlying stream.
+    /// Data written directly into the stream is not checked against `MAX_BYTES`.
+    pub fn get_mut(&mut self) -> &mut T {
+        return &mut self.writer.inner_mut().inner;
+    }
+
+    /// Flushes the underlying stream.
+    pub fn flush(&mut self) -> Result<(), Error> {
+        self.writer.inner_mut().flush()?;
+        return Ok(());
+    }
+
+    /// Returns the underlying stream.
+    /// Nothing is buffered by the writer, so no data is lost.
+    ///
+    /// Writing another document into the same stream is possible deliberately
+    /// by passing the stream to a new `SiteMapWriter`.
+    pub fn into_inner(self) -> T {
+        return self.writer.into_inner().inner;
+    }
+
+    fn bytes_written(&self) -> usize {
+        return self.writer.inner_ref().bytes;
+    }
+
+    fn into_state<N>(self) -> SiteMapWriter<T, N> {
+        SiteMapWriter {
+            writer: self.writer,
+            config: self.config,
+            scope: self.scope,
+            state: PhantomData,
+        }
+    }
 }
 
 impl<T: Write + Sized> SiteMapWriter<T> {
     /// Creates new sitemap writer
     pub fn new(out: T) -> SiteMapWriter<T> {
-        let writer = EmitterConfig::new().perform_indent(true).create_writer(out);
-        SiteMapWriter { writer: writer }
+        return SiteMapWriter::with_config(out, SiteMapWriterConfig::default());
+    }
+
+    /// Creates new sitemap writer with specified configuration
+    pub fn with_config(out: T, config: SiteMapWriterConfig) -> SiteMapWriter<T> {
+        let out = CountingWrite { inner: out, bytes: 0 };
+        let writer = XmlWriter::new(out, config.indent.as_deref(), &config.line_separator);
+        SiteMapWriter {
+            writer,
+            config,
+            scope: None,
+            state: PhantomData,
+        }
+    }
+
+    /// Restricts written urls to the scope of the sitemap.
+    /// `UrlSetWriter::url` returns `Error::ScopeViolation` for urls out of scope.
+    pub fn with_scope(mut self, scope: SiteMapScope) -> SiteMapWriter<T> {
+        self.scope = Some(scope);
+        return self;
     }
 
     /// Starts writing urls with sitemap namespace
@@ -46,14 +446,16 @@ impl<T: Write + Sized> SiteMapWriter<T> {
     /// Starts writing urls with custom sitemap namespace
     /// Adds specified namespace attribute for `urlset` tag
     pub fn start_urlset_ns(mut self, namespace: &str) -> Result<UrlSetWriter<T>, Error> {
-        self.writer.write(XmlEvent::start_element("urlset").default_ns(namespace))?;
-        Ok(UrlSetWriter { sitemap: self })
+        self.start_document()?;
+        self.writer.start_element("urlset", Some(namespace))?;
+        Ok(UrlSetWriter { sitemap: Some(self), count: 0 })
     }
 
     /// Starts writing urls without namespace
     pub fn start_urlset_without_ns(mut self) -> Result<UrlSetWriter<T>, Error> {
-        self.writer.write(XmlEvent::start_element("urlset"))?;
-        Ok(UrlSetWriter { sitemap: self })
+        self.start_document()?;
+        self.writer.start_element("urlset", None)?;
+        Ok(UrlSetWriter { sitemap: Some(self), count: 0 })
     }
 
     /// Starts writing sitemap urls
@@ -68,64 +470,294 @@ impl<T: Write + Sized> SiteMapWriter<T> {
         mut self,
         namespace: &str,
     ) -> Result<SiteMapIndexWriter<T>, Error> {
-        self.writer.write(XmlEvent::start_element("sitemapindex").default_ns(namespace))?;
-        Ok(SiteMapIndexWriter { sitemap: self })
+        self.start_document()?;
+        self.writer.start_element("sitemapindex", Some(namespace))?;
+        Ok(SiteMapIndexWriter { sitemap: Some(self), count: 0 })
     }
 
     /// Starts writing sitemap urls without namespace
     pub fn start_sitemapindex_without_ns(mut self) -> Result<SiteMapIndexWriter<T>, Error> {
-        self.writer.write(XmlEvent::start_element("sitemapindex"))?;
-        Ok(SiteMapIndexWriter { sitemap: self })
+        self.start_document()?;
+        self.writer.start_element("sitemapindex", None)?;
+        Ok(SiteMapIndexWriter { sitemap: Some(self), count: 0 })
+    }
+
+    /// Writes complete `urlset` document of the entries.
+    /// Returns the underlying stream and summary of the document.
+    pub fn write_urlset_from_iter<I>(self, urls: I) -> Result<(T, WriteSummary), Error>
+    where
+        I: IntoIterator,
+        I::Item: Into<UrlEntry>,
+    {
+        return self.try_write_urlset_from_iter(urls.into_iter().map(Ok::<_, Error>));
+    }
+
+    /// Writes complete `urlset` document of the entries, stopping at the first failed entry.
+    /// Returns the underlying stream and summary of the document.
+    pub fn try_write_urlset_from_iter<I, S, E>(self, urls: I) -> Result<(T, WriteSummary), E>
+    where
+        I: IntoIterator<Item = Result<S, E>>,
+        S: Into<UrlEntry>,
+        E: From<Error>,
+    {
+        let mut urlwriter = self.start_urlset()?;
+        for url in urls {
+            let written = match url {
+                Ok(url) => urlwriter.url(url).map_err(E::from),
+                Err(error) => Err(error),
+            };
+            if let Err(error) = written {
+                urlwriter.abandon();
+                return Err(error);
+            }
+        }
+        let count = urlwriter.count();
+        let mut sitemap = urlwriter.end()?;
+        sitemap.flush()?;
+        let summary = WriteSummary { count, bytes: sitemap.bytes_written() };
+        return Ok((sitemap.into_inner(), summary));
+    }
+
+    /// Writes complete `sitemapindex` document of the entries.
+    /// Returns the underlying stream and summary of the document.
+    pub fn write_index_from_iter<I>(self, sitemaps: I) -> Result<(T, WriteSummary), Error>
+    where
+        I: IntoIterator,
+        I::Item: Into<SiteMapEntry>,
+    {
+        return self.try_write_index_from_iter(sitemaps.into_iter().map(Ok::<_, Error>));
+    }
+
+    /// Writes complete `sitemapindex` document of the entries, stopping at the first failed entry.
+    /// Returns the underlying stream and summary of the document.
+    pub fn try_write_index_from_iter<I, S, E>(self, sitemaps: I) -> Result<(T, WriteSummary), E>
+    where
+        I: IntoIterator<Item = Result<S, E>>,
+        S: Into<SiteMapEntry>,
+        E: From<Error>,
+    {
+        let mut sitemap_index_writer = self.start_sitemapindex()?;
+        for sitemap in sitemaps {
+            let written = match sitemap {
+                Ok(sitemap) => sitemap_index_writer.sitemap(sitemap).map_err(E::from),
+                Err(error) => Err(error),
+            };
+            if let Err(error) = written {
+                sitemap_index_writer.abandon();
+                return Err(error);
+            }
+        }
+        let count = sitemap_index_writer.count();
+        let mut sitemap = sitemap_index_writer.end()?;
+        sitemap.flush()?;
+        let summary = WriteSummary { count, bytes: sitemap.bytes_written() };
+        return Ok((sitemap.into_inner(), summary));
+    }
+
+    /// Writes XML declaration, stylesheets and comments preceding the root element.
+    fn start_document(&mut self) -> Result<(), Error> {
+        if !is_utf8_label(&self.config.encoding) {
+            return Err(Error::UnsupportedEncoding { encoding: self.config.encoding.clone() });
+        }
+        if self.config.declaration {
+            self.writer.start_document(self.config.version, &self.config.encoding, self.config.standalone)?;
+        }
+        for (mime_type, href) in &self.config.stylesheets {
+            let data = format!(
+                "type=\"{}\" href=\"{}\"",
+                escape_attribute(mime_type),
+                escape_attribute(href),
+            );
+            self.writer.processing_instruction("xml-stylesheet", &data)?;
+        }
+        for comment in &self.config.comments {
+            self.writer.comment(comment)?;
+        }
+        Ok(())
     }
 
     fn write_content_element(&mut self, ele: &str, content: &str) -> Result<(), Error> {
-        self.writer.write(XmlEvent::start_element(ele))?;
-        self.writer.write(XmlEvent::characters(content))?;
-        self.writer.write(XmlEvent::end_element().name(ele))?;
+        self.writer.start_element(ele, None)?;
+        self.writer.characters(content)?;
+        self.writer.end_element(ele)?;
+        Ok(())
+    }
+
+    fn write_entry(&mut self, ele: &str, fields: &[(&str, String)]) -> Result<(), Error> {
+        self.writer.start_element(ele, None)?;
+        for (name, content) in fields {
+            self.write_content_element(name, content)?;
+        }
+        self.writer.end_element(ele)?;
+        Ok(())
+    }
+
+    /// Closes the root element of the writer dropped without `end()`.
+    /// Unless `warn` is false, the unfinished document is reported as a `log` warning.
+    fn drop_unfinished(&mut self, root: &str, writer: &str, warn: bool) {
+        if self.config.close_on_drop {
+            let _ = self.writer.end_element(root);
+            let _ = self.writer.inner_mut().flush();
+        } else if warn && !std::thread::panicking() {
+            log::warn!("sitemap: {} dropped without end(), document lacks </{}>", writer, root);
+        }
+    }
+
+    /// Returns bytes left for entries, keeping place for the `root` close tag.
+    fn remaining_bytes(&self, root: &str) -> usize {
+        let close_len = self.config.newline_len(0) + root.len() + 3;
+        return self.config.max_bytes.saturating_sub(self.bytes_written() + close_len);
+    }
+
+    /// Checks that one more entry fits into the protocol limits.
+    fn check_limits(
+        &self,
+        count: usize,
+        root: &str,
+        ele: &str,
+        fields: &[(&str, String)],
+    ) -> Result<(), Error> {
+        if count >= self.config.max_entries {
+            return Err(Error::LimitExceeded(Limit::Entries(self.config.max_entries)));
+        }
+        if self.config.entry_len(ele, fields) > self.remaining_bytes(root) {
+            return Err(Error::LimitExceeded(Limit::Bytes(self.config.max_bytes)));
+        }
         Ok(())
     }
 }
 
 /// Writes urls into writer.
+///
+/// Call `end()` or `finish()` to close the `urlset` element.
+#[must_use = "the document is incomplete until `end()` or `finish()` is called"]
 pub struct UrlSetWriter<T: Write + Sized> {
-    sitemap: SiteMapWriter<T>,
+    // Taken by `end()`, present until then.
+    sitemap: Option<SiteMapWriter<T>>,
+    count: usize,
 }
 
 impl<T: Write + Sized> UrlSetWriter<T> {
     /// Writes page url.
     pub fn url<S: Into<UrlEntry>>(&mut self, url: S) -> Result<(), Error> {
         let url = url.into();
-        self.sitemap.writer.write(XmlEvent::start_element("url"))?;
+        let sitemap = self.sitemap.as_mut().expect("urlset is ended");
+        if let (Some(scope), Location::Url(loc)) = (&sitemap.scope, &url.loc) {
+            if !scope.contains(loc) {
+                return Err(Error::ScopeViolation(loc.clone()));
+            }
+        }
+        let mut fields = Vec::new();
         if let Location::Url(loc) = url.loc {
-            self.sitemap.write_content_element("loc", loc.as_str())?;
+            fields.push(("loc", sitemap.config.location(&loc)));
         }
         if let LastMod::DateTime(lastmod) = url.lastmod {
-            self.sitemap.write_content_element("lastmod", lastmod.to_rfc3339().as_str())?;
+            fields.push(("lastmod", lastmod.to_rfc3339()));
         }
         match url.changefreq {
             ChangeFreq::ParseErr(_) => {}
             ChangeFreq::None => {}
-            _ => {
-                self.sitemap.write_content_element("changefreq", url.changefreq.as_str())?;
+            changefreq => {
+                fields.push(("changefreq", changefreq.to_string()));
             }
         }
         if let Priority::Value(priority) = url.priority {
-            self.sitemap.write_content_element("priority", priority.to_string().as_str())?;
+            fields.push(("priority", priority.to_string()));
         }
-        self.sitemap.writer.write(XmlEvent::end_element().name("url"))?;
+        sitemap.check_limits(self.count, "urlset", "url", &fields)?;
+        sitemap.write_entry("url", &fields)?;
+        self.count += 1;
         Ok(())
     }
 
+    /// Returns number of written urls.
+    pub fn count(&self) -> usize {
+        return self.count;
+    }
+
+    /// Returns number of bytes written so far.
+    pub fn bytes_written(&self) -> usize {
+        return self.writer().bytes_written();
+    }
+
+    /// Returns number of urls which may be added before reaching the entries limit.
+    pub fn remaining_urls(&self) -> usize {
+        return self.writer().config.max_entries.saturating_sub(self.count);
+    }
+
+    /// Returns number of bytes which may be added before reaching the size limit.
+    /// Close tag of `urlset` is taken into account.
+    pub fn remaining_bytes(&self) -> usize {
+        return self.writer().remaining_bytes("urlset");
+    }
+
     /// Completes writing data.
-    pub fn end(mut self) -> Result<SiteMapWriter<T>, Error> {
-        self.sitemap.writer.write(XmlEvent::end_element().name("urlset"))?;
-        Ok(self.sitemap)
+    pub fn end(mut self) -> Result<SiteMapWriter<T, Ended>, Error> {
+        let mut sitemap = self.sitemap.take().expect("urlset is ended");
+        sitemap.writer.end_element("urlset")?;
+        Ok(sitemap.into_state())
+    }
+
+    /// Completes writing data, flushes and returns the underlying stream.
+    pub fn finish(self) -> Result<T, Error> {
+        let mut sitemap = self.end()?;
+        sitemap.flush()?;
+        return Ok(sitemap.into_inner());
+    }
+
+    /// Returns reference to the underlying stream.
+    pub fn get_ref(&self) -> &T {
+        return self.writer().get_ref();
+    }
+
+    /// Returns mutable reference to the underlying stream.
+    /// Data written directly into the stream is not checked against `MAX_BYTES`.
+    pub fn get_mut(&mut self) -> &mut T {
+        return self.sitemap.as_mut().expect("urlset is ended").get_mut();
+    }
+
+    /// Flushes the underlying stream.
+    pub fn flush(&mut self) -> Result<(), Error> {
+        return self.sitemap.as_mut().expect("urlset is ended").flush();
+    }
+
+    fn writer(&self) -> &SiteMapWriter<T> {
+        return self.sitemap.as_ref().expect("urlset is ended");
+    }
+
+    /// Drops the writer after a failed entry, the error is returned to the caller instead of the warning.
+    fn abandon(mut self) {
+        if let Some(mut sitemap) = self.sitemap.take() {
+            sitemap.drop_unfinished("urlset", "UrlSetWriter", false);
+        }
+    }
+}
+
+impl<T: Write + Sized> Drop for UrlSetWriter<T> {
+    fn drop(&mut self) {
+        if let Some(ref mut sitemap) = self.sitemap {
+            sitemap.drop_unfinished("urlset", "UrlSetWriter", true);
+        }
+    }
+}
+
+#[cfg(feature = "output")]
+impl UrlSetWriter<AtomicFile> {
+    /// Completes writing data and atomically publishes the file.
+    /// The file is left untouched when writing fails.
+    pub fn commit(self) -> Result<(), Error> {
+        return self.finish()?.commit();
     }
 }
 
 /// Writes sitemaps list into writer.
+///
+/// Call `end()` or `finish()` to close the `sitemapindex` element.
+#[must_use = "the document is incomplete until `end()` or `finish()` is called"]
 pub struct SiteMapIndexWriter<T: Write + Sized> {
-    sitemap: SiteMapWriter<T>,
+    // Taken by `end()`, present until then.
+    sitemap: Option<SiteMapWriter<T>>,
+    count: usize,
 }
 
 impl<T: Write + Sized> SiteMapIndexWriter<T> {
@@ -133,20 +765,171 @@ impl<T: Write + Sized> SiteMapIndexWriter<T> {
     /// Writes sitemap entry.
     pub fn sitemap<S: Into<SiteMapEntry>>(&mut self, sitemapentry: S) -> Result<(), Error> {
         let sitemapentry = sitemapentry.into();
-        self.sitemap.writer.write(XmlEvent::start_element("sitemap"))?;
+        let sitemap = self.sitemap.as_mut().expect("sitemapindex is ended");
+        let mut fields = Vec::new();
         if let Location::Url(loc) = sitemapentry.loc {
-            self.sitemap.write_content_element("loc", loc.as_str())?;
+            fields.push(("loc", sitemap.config.location(&loc)));
         }
         if let LastMod::DateTime(lastmod) = sitemapentry.lastmod {
-            self.sitemap.write_content_element("lastmod", lastmod.to_rfc3339().as_str())?;
+            fields.push(("lastmod", lastmod.to_rfc3339()));
         }
-        self.sitemap.writer.write(XmlEvent::end_element().name("sitemap"))?;
+        sitemap.check_limits(self.count, "sitemapindex", "sitemap", &fields)?;
+        sitemap.write_entry("sitemap", &fields)?;
+        self.count += 1;
         Ok(())
     }
 
+    /// Returns number of written sitemaps.
+    pub fn count(&self) -> usize {
+        return self.count;
+    }
+
+    /// Returns number of bytes written so far.
+    pub fn bytes_written(&self) -> usize {
+        return self.writer().bytes_written();
+    }
+
+    /// Returns number of sitemaps which may be added before reaching the entries limit.
+    pub fn remaining_sitemaps(&self) -> usize {
+        return self.writer().config.max_entries.saturating_sub(self.count);
+    }
+
+    /// Returns number of bytes which may be added before reaching the size limit.
+    /// Close tag of `sitemapindex` is taken into account.
+    pub fn remaining_bytes(&self) -> usize {
+        return self.writer().remaining_bytes("sitemapindex");
+    }
+
     /// Completes writing data.
-    pub fn end(mut self) -> Result<SiteMapWriter<T>, Error> {
-        self.sitemap.writer.write(XmlEvent::end_element().name("sitemapindex"))?;
-        Ok(self.sitemap)
+    pub fn end(mut self) -> Result<SiteMapWriter<T, Ended>, Error> {
+        let mut sitemap = self.sitemap.take().expect("sitemapindex is ended");
+        sitemap.writer.end_element("sitemapindex")?;
+        Ok(sitemap.into_state())
+    }
+
+    /// Completes writing data, flushes and returns the underlying stream.
+    pub fn finish(self) -> Result<T, Error> {
+        let mut sitemap = self.end()?;
+        sitemap.flush()?;
+        return Ok(sitemap.into_inner());
+    }
+
+    /// Returns reference to the underlying stream.
+    pub fn get_ref(&self) -> &T {
+        return self.writer().get_ref();
+    }
+
+    /// Returns mutable reference to the underlying stream.
+    /// Data written directly into the stream is not checked against `MAX_BYTES`.
+    pub fn get_mut(&mut self) -> &mut T {
+        return self.sitemap.as_mut().expect("sitemapindex is ended").get_mut();
+    }
+
+    /// Flushes the underlying stream.
+    pub fn flush(&mut self) -> Result<(), Error> {
+        return self.sitemap.as_mut().expect("sitemapindex is ended").flush();
+    }
+
+    fn writer(&self) -> &SiteMapWriter<T> {
+        return self.sitemap.as_ref().expect("sitemapindex is ended");
+    }
+
+    /// Drops the writer after a failed entry, the error is returned to the caller instead of the warning.
+    fn abandon(mut self) {
+        if let Some(mut sitemap) = self.sitemap.take() {
+            sitemap.drop_unfinished("sitemapindex", "SiteMapIndexWriter", false);
+        }
+    }
+}
+
+impl<T: Write + Sized> Drop for SiteMapIndexWriter<T> {
+    fn drop(&mut self) {
+        if let Some(ref mut sitemap) = self.sitemap {
+            sitemap.drop_unfinished("sitemapindex", "SiteMapIndexWriter", true);
+        }
+    }
+}
+
+#[cfg(feature = "output")]
+impl SiteMapIndexWriter<AtomicFile> {
+    /// Completes writing data and atomically publishes the file.
+    /// The file is left untouched when writing fails.
+    pub fn commit(self) -> Result<(), Error> {
+        return self.finish()?.commit();
+    }
+}
+
+/// Writes a sequence of separate documents, each into a new stream.
+///
+/// Streams are created by the factory, which receives the number of the document counting from 0.
+/// Every document is written with the same configuration and scope.
+///
+/// # Examples
+///
+/// ```rust
+/// use sitemap::writer::SiteMapSequence;
+/// fn main() {
+///     let mut sequence = SiteMapSequence::new(|_| Ok(Vec::new()));
+///     let mut documents = Vec::new();
+///     for part in ["http://example.com/a", "http://example.com/b"] {
+///         let (document, _) = sequence.next_document().unwrap()
+///             .write_urlset_from_iter([part])
+///             .unwrap();
+///         documents.push(document);
+///     }
+///     assert_eq!(sequence.count(), 2);
+/// }
+/// ```
+pub struct SiteMapSequence<T: Write + Sized, F: FnMut(usize) -> Result<T, Error>> {
+    factory: F,
+    config: SiteMapWriterConfig,
+    scope: Option<SiteMapScope>,
+    count: usize,
+}
+
+impl<T: Write + Sized, F: FnMut(usize) -> Result<T, Error>> SiteMapSequence<T, F> {
+    /// Creates new sequence of documents with default configuration.
+    pub fn new(factory: F) -> SiteMapSequence<T, F> {
+        return SiteMapSequence::with_config(factory, SiteMapWriterConfig::default());
+    }
+
+    /// Creates new sequence of documents with specified configuration.
+    pub fn with_config(factory: F, config: SiteMapWriterConfig) -> SiteMapSequence<T, F> {
+        SiteMapSequence {
+            factory,
+            config,
+            scope: None,
+            count: 0,
+        }
+    }
+
+    /// Restricts written urls of every document to the scope of the sitemap.
+    pub fn with_scope(mut self, scope: SiteMapScope) -> SiteMapSequence<T, F> {
+        self.scope = Some(scope);
+        return self;
+    }
+
+    /// Creates stream of the next document and returns writer of the document.
+    pub fn next_document(&mut self) -> Result<SiteMapWriter<T>, Error> {
+        let out = (self.factory)(self.count)?;
+        self.count += 1;
+        let mut writer = SiteMapWriter::with_config(out, self.config.clone());
+        writer.scope = self.scope.clone();
+        return Ok(writer);
+    }
+
+    /// Returns number of created documents.
+    pub fn count(&self) -> usize {
+        return self.count;
+    }
+}
+
+impl<T: Write + Sized, F: FnMut(usize) -> Result<T, Error>> fmt::Debug for SiteMapSequence<T, F> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("SiteMapSequence")
+            .field("config", &self.config)
+            .field("scope", &self.scope)
+            .field("count", &self.count)
+            .finish_non_exhaustive()
     }
 }
@@ -0,0 +1,90 @@
+use sitemap::reader::{SiteMapReader, SiteMapEntity};
+use sitemap::structs::UrlEntry;
+use sitemap::writer::{SiteMapWriter, SiteMapWriterConfig};
+use sitemap::Error;
+
+fn read_urls(content: &[u8]) -> Vec<String> {
+    let entities: Vec<SiteMapEntity> = SiteMapReader::new(content).collect();
+    read_entities(entities)
+}
+
+fn read_entities(entities: Vec<SiteMapEntity>) -> Vec<String> {
+    entities.into_iter()
+        .map(|entity| match entity {
+            SiteMapEntity::Url(url) => url.loc.get_url().unwrap().to_string(),
+            other => panic!("expected url, got {:?}", other),
+        })
+        .collect()
+}
+
+fn utf16(content: &str, big_endian: bool, bom: bool) -> Vec<u8> {
+    let mut data = Vec::new();
+    if bom {
+        data.extend_from_slice(if big_endian { b"\xFE\xFF" } else { b"\xFF\xFE" });
+    }
+    for unit in content.encode_utf16() {
+        let bytes = if big_endian { unit.to_be_bytes() } else { unit.to_le_bytes() };
+        data.extend_from_slice(&bytes);
+    }
+    data
+}
+
+static CONTENT: &str = "<?xml version=\"1.0\" encoding=\"UTF-16\"?>
+<urlset><url><loc>http://www.example.com/привет</loc></url></urlset>";
+
+const EXPECTED: &str = "http://www.example.com/%D0%BF%D1%80%D0%B8%D0%B2%D0%B5%D1%82";
+
+#[test]
+fn test_encoding_legacy() {
+    let mut latin1 = b"<?xml version='1.0' encoding='ISO-8859-1'?>\n<urlset><url><loc>http://www.example.com/caf".to_vec();
+    latin1.extend_from_slice(b"\xE9</loc></url></urlset>");
+    assert_eq!(read_urls(&latin1), ["http://www.example.com/caf%C3%A9"]);
+
+    let mut cp1251 = b"<?xml version=\"1.0\" encoding=\"windows-1251\"?>\n<urlset><url><loc>http://www.example.com/".to_vec();
+    cp1251.extend_from_slice(b"\xEF\xF0\xE8\xE2\xE5\xF2</loc></url></urlset>");
+    assert_eq!(read_urls(&cp1251), [EXPECTED]);
+}
+
+#[test]
+fn test_encoding_bom() {
+    let mut utf8 = b"\xEF\xBB\xBF".to_vec();
+    utf8.extend_from_slice(CONTENT.replace("UTF-16", "UTF-8").as_bytes());
+    assert_eq!(read_urls(&utf8), [EXPECTED]);
+    assert_eq!(read_urls(&utf16(CONTENT, false, true)), [EXPECTED]);
+    assert_eq!(read_urls(&utf16(CONTENT, true, true)), [EXPECTED]);
+    assert_eq!(read_urls(&utf16(CONTENT, false, false)), [EXPECTED]);
+}
+
+#[test]
+fn test_encoding_mislabeled_utf8() {
+    // Both backends accept UTF-8 documents declaring UTF-16 or another encoding than the byte order mark.
+    assert_eq!(read_urls(CONTENT.as_bytes()), [EXPECTED]);
+    let mut utf8 = b"\xEF\xBB\xBF".to_vec();
+    utf8.extend_from_slice(CONTENT.replace("UTF-16", "windows-1251").as_bytes());
+    assert_eq!(read_urls(&utf8), [EXPECTED]);
+}
+
+#[test]
+fn test_encoding_unsupported() {
+    let content = "<?xml version=\"1.0\" encoding=\"x-unknown\"?><urlset/>";
+    let entities: Vec<SiteMapEntity> = SiteMapReader::new(content.as_bytes()).collect();
+    match &entities[..] {
+        [SiteMapEntity::Err(Error::UnsupportedEncoding { encoding })] => assert_eq!(encoding, "x-unknown"),
+        other => panic!("expected unsupported encoding, got {:?}", other),
+    }
+
+    let config = SiteMapWriterConfig::new().encoding("windows-1251");
+    let result = SiteMapWriter::with_config(Vec::new(), config).write_urlset_from_iter([UrlEntry::from("http://www.example.com/")]);
+    assert!(matches!(result, Err(Error::UnsupportedEncoding { .. })));
+    let config = SiteMapWriterConfig::new().encoding("utf8");
+    let (output, _) = SiteMapWriter::with_config(Vec::new(), config).write_urlset_from_iter(Vec::<UrlEntry>::new()).unwrap();
+    assert!(String::from_utf8(output).unwrap().starts_with("<?xml version=\"1.0\" encoding=\"utf8\"?>"));
+}
+
+#[cfg(feature = "quick-xml")]
+#[test]
+fn test_encoding_fast_reader() {
+    use sitemap::fast_reader::FastSiteMapReader;
+    let entities: Vec<SiteMapEntity> = FastSiteMapReader::new(&utf16(CONTENT, true, true)[..]).collect();
+    assert_eq!(read_entities(entities), [EXPECTED]);
+}
@@ -0,0 +1,141 @@
+#![cfg(feature = "quick-xml")]
+use sitemap::fast_reader::FastSiteMapReader;
+use sitemap::reader::{SiteMapReader, SiteMapEntity, ReaderLimits};
+use sitemap::scope::SiteMapScope;
+use sitemap::structs::ChangeFreq;
+use sitemap::{Error, Limit};
+use std::fs::File;
+use std::io::BufReader;
+use url::Url;
+
+static CONTENT: &str =
+    "<?xml version=\"1.0\" encoding=\"UTF-8\"?>
+<urlset xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\"
+        xmlns:image=\"http://www.google.com/schemas/sitemap-image/1.1\">
+  <url>
+    <loc>http://www.example.com/?a=1&amp;b=2</loc>
+    <image:image><image:loc>http://www.example.com/image.png</image:loc></image:image>
+    <changefreq><![CDATA[weekly]]></changefreq>
+  </url>
+  <url>
+    <loc>http://www.example.com/invalid.html</loc>
+    <lastmod>yesterday</lastmod>
+    <priority>1.5</priority>
+  </url>
+  <url>
+    <loc>http://www.example.org/other.html</loc>
+  </url>
+  <url/>
+</urlset>";
+
+#[test]
+fn test_fast_reader_same_entities() {
+    let file = File::open("tests/documents/sitemap1.xml").unwrap();
+    let expected: Vec<SiteMapEntity> = SiteMapReader::new(file).collect();
+    let file = File::open("tests/documents/sitemap1.xml").unwrap();
+    let entities: Vec<SiteMapEntity> = FastSiteMapReader::new(BufReader::new(file)).collect();
+    assert_eq!(entities.len(), 7);
+    assert_eq!(format!("{:?}", entities), format!("{:?}", expected));
+
+    let content = "<urlset><url><loc>http://www.example.com/<![CDATA[a]]><x>b</x>c</loc>
+<lastmod> </lastmod></url></urlset>";
+    let expected: Vec<SiteMapEntity> = SiteMapReader::new(content.as_bytes()).collect();
+    let entities: Vec<SiteMapEntity> = FastSiteMapReader::new(content.as_bytes()).collect();
+    assert_eq!(format!("{:?}", entities), format!("{:?}", expected));
+}
+
+#[test]
+fn test_fast_reader_entries() {
+    let scope = SiteMapScope::new(Url::parse("http://www.example.com/").unwrap());
+    let entities: Vec<SiteMapEntity> = FastSiteMapReader::new(CONTENT.as_bytes()).with_scope(scope.clone()).collect();
+    assert_eq!(entities.len(), 4);
+    match &entities[0] {
+        SiteMapEntity::Url(entry) => {
+            assert_eq!(entry.loc.get_url().unwrap().as_str(), "http://www.example.com/?a=1&b=2");
+            assert_eq!(entry.changefreq, ChangeFreq::Weekly);
+        }
+        other => panic!("expected url, got {:?}", other),
+    }
+    assert!(matches!(entities[2], SiteMapEntity::OutOfScope(_)));
+
+    let entities: Vec<SiteMapEntity> = FastSiteMapReader::new(CONTENT.as_bytes())
+        .with_scope(scope.clone())
+        .with_validation()
+        .collect();
+    let expected: Vec<SiteMapEntity> = SiteMapReader::new(CONTENT.as_bytes())
+        .with_scope(scope)
+        .with_validation()
+        .collect();
+    assert_eq!(format!("{:?}", entities), format!("{:?}", expected));
+    match &entities[0] {
+        SiteMapEntity::InvalidUrl { problems, .. } => match &problems[..] {
+            [Error::UnsupportedExtension { namespace }] => {
+                assert_eq!(namespace, "http://www.google.com/schemas/sitemap-image/1.1");
+            }
+            other => panic!("expected unsupported extension, got {:?}", other),
+        },
+        other => panic!("expected invalid url, got {:?}", other),
+    }
+    let problems: Vec<usize> = entities.iter()
+        .map(|entity| match entity {
+            SiteMapEntity::Url(_) => 0,
+            SiteMapEntity::InvalidUrl { problems, .. } => problems.len(),
+            other => panic!("unexpected entity {:?}", other),
+        })
+        .collect();
+    assert_eq!(problems, [1, 2, 1, 1]);
+
+    let legacy = "<urlset xmlns=\"http://www.google.com/schemas/sitemap/0.84\">
+  <url><loc>http://www.example.com/</loc><lastmod>2005-01-01</lastmod></url>
+</urlset>";
+    let entities: Vec<SiteMapEntity> = FastSiteMapReader::new(legacy.as_bytes()).with_validation().collect();
+    assert!(matches!(entities[..], [SiteMapEntity::Url(_)]), "{:?}", entities);
+}
+
+#[test]
+fn test_fast_reader_syntax_error() {
+    let content = "<?xml version=\"1.0\" encoding=\"UTF-8\"?>
+<urlset xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\">
+  <url><loc>http://www.example.com/</loc></url>
+  <url><loc>http://www.example.com/other</url>
+</urlset>";
+    let entities: Vec<SiteMapEntity> = FastSiteMapReader::new(content.as_bytes()).collect();
+    assert_eq!(entities.len(), 2);
+    assert!(matches!(entities[0], SiteMapEntity::Url(_)));
+    match &entities[1] {
+        SiteMapEntity::Err(Error::XmlSyntax { line, .. }) => assert_eq!(*line, 4),
+        other => panic!("expected syntax error, got {:?}", other),
+    }
+
+    let content = "<urlset><url><loc>http://www.example.com/</loc></url>";
+    let entities: Vec<SiteMapEntity> = FastSiteMapReader::new(content.as_bytes()).collect();
+    assert!(matches!(entities[1], SiteMapEntity::Err(Error::XmlSyntax { .. })));
+}
+
+#[test]
+fn test_fast_reader_limits() {
+    let limits = [
+        (ReaderLimits::new().max_entries(2), Limit::Entries(2)),
+        (ReaderLimits::new().max_bytes(300), Limit::Bytes(300)),
+        (ReaderLimits::new().max_field_length(16), Limit::FieldLength(16)),
+        (ReaderLimits::new().max_depth(2), Limit::Depth(2)),
+    ];
+    for (limits, limit) in limits {
+        let slow: Vec<SiteMapEntity> = SiteMapReader::new(CONTENT.as_bytes()).with_limits(limits.clone()).collect();
+        let fast: Vec<SiteMapEntity> = FastSiteMapReader::new(CONTENT.as_bytes()).with_limits(limits).collect();
+        assert_eq!(fast.len(), slow.len(), "{:?}", limit);
+        match fast.last() {
+            Some(SiteMapEntity::Err(Error::LimitExceeded(exceeded))) => assert_eq!(*exceeded, limit),
+            other => panic!("expected limit of {:?}, got {:?}", limit, other),
+        }
+    }
+}
+
+#[test]
+fn test_fast_reader_doctype() {
+    let content = "<!DOCTYPE urlset [<!ENTITY a \"aaaaaaaaaa\">]>
+<urlset><url><loc>http://www.example.com/&a;</loc></url></urlset>";
+    let entities: Vec<SiteMapEntity> = FastSiteMapReader::new(content.as_bytes()).collect();
+    assert_eq!(entities.len(), 1);
+    assert!(matches!(entities[0], SiteMapEntity::Err(Error::XmlSyntax { .. })));
+}
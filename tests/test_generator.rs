@@ -0,0 +1,153 @@
+#![cfg(feature = "generator")]
+use sitemap::generator::{generate_from_dir, write_from_dir, write_sequence_from_dir, GeneratorOptions, UrlStyle};
+use sitemap::structs::LastMod;
+use sitemap::writer::{SiteMapSequence, SiteMapWriter, SiteMapWriterConfig};
+use sitemap::Error;
+use std::fs;
+use std::path::Path;
+use url::Url;
+
+fn create_site() -> tempfile::TempDir {
+    let root = tempfile::tempdir().unwrap();
+    let files = [
+        "index.html",
+        "about.html",
+        "style.css",
+        "blog/index.html",
+        "blog/first post.html",
+        "blog/second.htm",
+        "drafts/index.html",
+        "docs/404.html",
+        "docs/guide/index.html",
+    ];
+    for file in files.iter() {
+        let path = root.path().join(file);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, "<html></html>").unwrap();
+    }
+    root
+}
+
+fn urls(root: &Path, options: &GeneratorOptions) -> Vec<String> {
+    let base_url = Url::parse("https://www.example.com/site/").unwrap();
+    generate_from_dir(root, &base_url, options)
+        .unwrap()
+        .iter()
+        .map(|entry| entry.loc.to_string())
+        .collect()
+}
+
+#[test]
+fn test_generate_urls() {
+    let site = create_site();
+    let options = GeneratorOptions::new().ignore("drafts").ignore("**/404.html");
+    assert_eq!(urls(site.path(), &options), vec![
+        "https://www.example.com/site/about.html",
+        "https://www.example.com/site/blog/first%20post.html",
+        "https://www.example.com/site/blog/",
+        "https://www.example.com/site/blog/second.htm",
+        "https://www.example.com/site/docs/guide/",
+        "https://www.example.com/site/",
+    ]);
+
+    let options = options.url_style(UrlStyle::StripExtension);
+    assert_eq!(urls(site.path(), &options), vec![
+        "https://www.example.com/site/about",
+        "https://www.example.com/site/blog/first%20post",
+        "https://www.example.com/site/blog/",
+        "https://www.example.com/site/blog/second",
+        "https://www.example.com/site/docs/guide/",
+        "https://www.example.com/site/",
+    ]);
+
+    let options = GeneratorOptions::new().ignore("*/index.html").url_style(UrlStyle::TrailingSlash);
+    assert_eq!(urls(site.path(), &options), vec![
+        "https://www.example.com/site/about/",
+        "https://www.example.com/site/blog/first%20post/",
+        "https://www.example.com/site/blog/second/",
+        "https://www.example.com/site/docs/404/",
+        "https://www.example.com/site/docs/guide/",
+        "https://www.example.com/site/",
+    ]);
+}
+
+#[test]
+fn test_generate_lastmod() {
+    let site = create_site();
+    let base_url = Url::parse("https://www.example.com").unwrap();
+    let entries = generate_from_dir(site.path(), &base_url, &GeneratorOptions::new()).unwrap();
+    assert!(entries.iter().all(|entry| matches!(entry.lastmod, LastMod::DateTime(_))));
+    let entries = generate_from_dir(site.path(), &base_url, &GeneratorOptions::new().lastmod(false)).unwrap();
+    assert!(entries.iter().all(|entry| entry.lastmod == LastMod::None));
+}
+
+#[test]
+fn test_generate_errors() {
+    let site = create_site();
+    let base_url = Url::parse("https://www.example.com/").unwrap();
+    let options = GeneratorOptions::new().ignore("[");
+    assert!(matches!(generate_from_dir(site.path(), &base_url, &options), Err(Error::InvalidGlob { ref pattern, .. }) if pattern == "["));
+    let missing = site.path().join("missing");
+    assert!(matches!(generate_from_dir(missing, &base_url, &GeneratorOptions::new()), Err(Error::Io(_))));
+    let base_url = Url::parse("mailto:webmaster@example.com").unwrap();
+    assert!(matches!(generate_from_dir(site.path(), &base_url, &GeneratorOptions::new()), Err(Error::InvalidUrl { .. })));
+}
+
+#[test]
+fn test_generate_write_urls() {
+    let site = create_site();
+    fs::remove_dir_all(site.path().join("blog")).unwrap();
+    fs::remove_dir_all(site.path().join("docs")).unwrap();
+    fs::remove_dir_all(site.path().join("drafts")).unwrap();
+    let base_url = Url::parse("https://www.example.com/").unwrap();
+    let mut output = Vec::<u8>::new();
+    {
+        let sitemap_writer = SiteMapWriter::new(&mut output);
+        let mut urlwriter = sitemap_writer.start_urlset().unwrap();
+        let options = GeneratorOptions::new().lastmod(false);
+        assert_eq!(write_from_dir(site.path(), &base_url, &options, &mut urlwriter).unwrap(), 2);
+        urlwriter.end().unwrap();
+    }
+    let result = String::from_utf8_lossy(&output);
+    assert!(result.contains("<loc>https://www.example.com/about.html</loc>"));
+    assert!(result.contains("<loc>https://www.example.com/</loc>"));
+}
+
+#[test]
+fn test_generate_write_sequence() {
+    let site = create_site();
+    let base_url = Url::parse("https://www.example.com/").unwrap();
+    let options = GeneratorOptions::new().lastmod(false).ignore("drafts");
+    let config = SiteMapWriterConfig::new().max_entries(3);
+    let mut sequence = SiteMapSequence::with_config(|_| Ok(Vec::new()), config);
+    let documents = write_sequence_from_dir(site.path(), &base_url, &options, &mut sequence).unwrap();
+    assert_eq!(sequence.count(), 3);
+    let documents: Vec<String> = documents.into_iter().map(|document| String::from_utf8(document).unwrap()).collect();
+    assert_eq!(documents.iter().map(|document| document.matches("<url>").count()).collect::<Vec<_>>(), vec![3, 3, 1]);
+    assert!(documents[0].contains("<loc>https://www.example.com/about.html</loc>"));
+    assert!(documents[2].ends_with("</urlset>"));
+
+    let config = SiteMapWriterConfig::new().max_bytes(400);
+    let mut sequence = SiteMapSequence::with_config(|_| Ok(Vec::new()), config);
+    let documents = write_sequence_from_dir(site.path(), &base_url, &options, &mut sequence).unwrap();
+    assert!(documents.len() > 1);
+    assert!(documents.iter().all(|document| document.len() <= 400));
+    let urls: usize = documents.iter().map(|document| String::from_utf8_lossy(document).matches("<url>").count()).sum();
+    assert_eq!(urls, 7);
+
+    let empty = tempfile::tempdir().unwrap();
+    let mut sequence = SiteMapSequence::new(|_| Ok(Vec::new()));
+    assert!(write_sequence_from_dir(empty.path(), &base_url, &options, &mut sequence).unwrap().is_empty());
+}
+
+#[cfg(unix)]
+#[test]
+fn test_generate_dangling_symlink() {
+    let site = create_site();
+    std::os::unix::fs::symlink(site.path().join("missing.html"), site.path().join("dangling.html")).unwrap();
+    let options = GeneratorOptions::new().ignore("drafts").ignore("docs").ignore("blog");
+    assert_eq!(urls(site.path(), &options), vec![
+        "https://www.example.com/site/about.html",
+        "https://www.example.com/site/",
+    ]);
+}
@@ -0,0 +1,95 @@
+#![cfg(feature = "incremental")]
+use sitemap::incremental::{IncrementalWriter, Regeneration};
+use sitemap::provider::{MemoryProvider, SiteMapPaginator};
+use sitemap::structs::UrlEntry;
+use chrono::{DateTime, FixedOffset};
+use std::fs;
+use std::path::Path;
+use url::Url;
+
+fn time(time: &str) -> DateTime<FixedOffset> {
+    DateTime::parse_from_rfc3339(time).unwrap()
+}
+
+fn paginator(pages: &[&str]) -> SiteMapPaginator<MemoryProvider> {
+    let entries = pages.iter().map(|page| UrlEntry::from(format!("https://www.example.com/{}", page)));
+    SiteMapPaginator::new(MemoryProvider::new(entries), Url::parse("https://www.example.com/").unwrap()).part_size(2)
+}
+
+fn read(directory: &Path, name: &str) -> String {
+    fs::read_to_string(directory.join(name)).unwrap()
+}
+
+#[test]
+fn test_incremental_regeneration() {
+    let directory = tempfile::tempdir().unwrap();
+    let writer = IncrementalWriter::new(directory.path());
+
+    let regeneration = writer.write(&paginator(&["a", "b", "c", "d", "e"]), time("2020-01-01T00:00:00+00:00")).unwrap();
+    assert_eq!(regeneration, Regeneration {
+        written: vec![1, 2, 3],
+        unchanged: vec![],
+        removed: vec![],
+        index_written: true,
+    });
+    assert!(read(directory.path(), "sitemap-3.xml").contains("<loc>https://www.example.com/e</loc>"));
+    assert!(read(directory.path(), "sitemap.xml").contains(
+        "<loc>https://www.example.com/sitemap-2.xml</loc>\n    <lastmod>2020-01-01T00:00:00+00:00</lastmod>"
+    ));
+
+    let regeneration = writer.write(&paginator(&["a", "b", "c", "d", "e"]), time("2020-01-02T00:00:00+00:00")).unwrap();
+    assert_eq!(regeneration, Regeneration {
+        written: vec![],
+        unchanged: vec![1, 2, 3],
+        removed: vec![],
+        index_written: false,
+    });
+
+    let regeneration = writer.write(&paginator(&["a", "b", "c", "x"]), time("2020-01-03T00:00:00+00:00")).unwrap();
+    assert_eq!(regeneration, Regeneration {
+        written: vec![2],
+        unchanged: vec![1],
+        removed: vec!["sitemap-3.xml".to_string()],
+        index_written: true,
+    });
+    assert!(!directory.path().join("sitemap-3.xml").exists());
+    assert!(read(directory.path(), "sitemap-2.xml").contains("<loc>https://www.example.com/x</loc>"));
+    let index = read(directory.path(), "sitemap.xml");
+    assert!(index.contains("<loc>https://www.example.com/sitemap-1.xml</loc>\n    <lastmod>2020-01-01T00:00:00+00:00</lastmod>"));
+    assert!(index.contains("<loc>https://www.example.com/sitemap-2.xml</loc>\n    <lastmod>2020-01-03T00:00:00+00:00</lastmod>"));
+    assert!(!index.contains("sitemap-3.xml"));
+
+    // Deleted parts are restored even when the manifest is up to date.
+    fs::remove_file(directory.path().join("sitemap-1.xml")).unwrap();
+    let regeneration = writer.write(&paginator(&["a", "b", "c", "x"]), time("2020-01-04T00:00:00+00:00")).unwrap();
+    assert_eq!(regeneration.written, vec![1]);
+    assert!(directory.path().join("sitemap-1.xml").exists());
+}
+
+#[test]
+fn test_incremental_manifest() {
+    let directory = tempfile::tempdir().unwrap();
+    let manifest = directory.path().join("state").join("manifest.txt");
+    fs::create_dir_all(manifest.parent().unwrap()).unwrap();
+    let writer = IncrementalWriter::new(directory.path()).manifest(&manifest);
+    writer.write(&paginator(&["a"]), time("2020-01-01T00:00:00+00:00")).unwrap();
+    assert!(read(directory.path(), "state/manifest.txt").contains("sitemap-1.xml "));
+    assert!(!directory.path().join("sitemap.manifest").exists());
+
+    fs::write(&manifest, "sitemap-1.xml broken\n").unwrap();
+    assert!(writer.write(&paginator(&["a"]), time("2020-01-01T00:00:00+00:00")).is_err());
+}
+
+#[cfg(feature = "gzip")]
+#[test]
+fn test_incremental_gzip() {
+    use flate2::read::GzDecoder;
+    use std::io::Read;
+    let directory = tempfile::tempdir().unwrap();
+    let paginator = paginator(&["a", "b", "c"]).gzip(true);
+    IncrementalWriter::new(directory.path()).write(&paginator, time("2020-01-01T00:00:00+00:00")).unwrap();
+    let mut part = String::new();
+    GzDecoder::new(fs::File::open(directory.path().join("sitemap-2.xml.gz")).unwrap()).read_to_string(&mut part).unwrap();
+    assert!(part.contains("<loc>https://www.example.com/c</loc>"));
+    assert!(read(directory.path(), "sitemap.xml").contains("<loc>https://www.example.com/sitemap-2.xml.gz</loc>"));
+}
@@ -0,0 +1,113 @@
+#![cfg(feature = "generator")]
+use sitemap::generator::{generate_from_dir, GeneratorOptions};
+use sitemap::lastmod::{FileTime, GitHistory, LastModProvider};
+use sitemap::structs::{LastMod, UrlEntry};
+use chrono::DateTime;
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+use url::Url;
+
+fn git(repository: &Path, args: &[&str], date: &str) {
+    let status = Command::new("git")
+        .arg("-C")
+        .arg(repository)
+        .args(["-c", "user.name=Test", "-c", "user.email=test@example.com", "-c", "commit.gpgsign=false"])
+        .args(args)
+        .env("GIT_AUTHOR_DATE", date)
+        .env("GIT_COMMITTER_DATE", date)
+        .status()
+        .unwrap();
+    assert!(status.success());
+}
+
+fn commit(repository: &Path, files: &[&str], date: &str) {
+    for file in files {
+        let path = repository.join(file);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, date).unwrap();
+    }
+    git(repository, &["add", "-A"], date);
+    git(repository, &["commit", "-q", "-m", date], date);
+}
+
+fn lastmod(time: &str) -> LastMod {
+    LastMod::DateTime(DateTime::parse_from_rfc3339(time).unwrap())
+}
+
+#[test]
+fn test_lastmod_git_history() {
+    let repository = tempfile::tempdir().unwrap();
+    let root = repository.path();
+    git(root, &["init", "-q"], "2020-01-01T00:00:00+00:00");
+    commit(root, &["index.html", "docs/guide.html", "docs/first step.html"], "2020-01-01T00:00:00+00:00");
+    commit(root, &["docs/guide.html"], "2021-06-01T12:00:00+03:00");
+    fs::write(root.join("draft.html"), "").unwrap();
+
+    let history = GitHistory::open(root.join("docs")).unwrap();
+    assert_eq!(history.toplevel(), fs::canonicalize(root).unwrap());
+    assert_eq!(history.lastmod(&root.join("index.html")).unwrap(), lastmod("2020-01-01T00:00:00+00:00"));
+    assert_eq!(history.lastmod(&root.join("docs/first step.html")).unwrap(), lastmod("2020-01-01T00:00:00+00:00"));
+    assert_eq!(history.lastmod(&root.join("docs/guide.html")).unwrap(), lastmod("2021-06-01T12:00:00+03:00"));
+    assert_eq!(history.lastmod(&root.join("draft.html")).unwrap(), LastMod::None);
+    assert_eq!(history.lastmod(&root.join("missing.html")).unwrap(), LastMod::None);
+
+    let mut entry = UrlEntry::from("https://www.example.com/docs/guide.html");
+    history.fill(&mut entry, &root.join("docs/guide.html")).unwrap();
+    assert_eq!(entry.lastmod, lastmod("2021-06-01T12:00:00+03:00"));
+
+    let base_url = Url::parse("https://www.example.com/").unwrap();
+    let options = GeneratorOptions::new().lastmod_provider(history);
+    let entries = generate_from_dir(root, &base_url, &options).unwrap();
+    let lastmods: Vec<(String, LastMod)> = entries
+        .into_iter()
+        .map(|entry| (entry.loc.to_string(), entry.lastmod))
+        .collect();
+    assert_eq!(lastmods, vec![
+        ("https://www.example.com/docs/first%20step.html".to_string(), lastmod("2020-01-01T00:00:00+00:00")),
+        ("https://www.example.com/docs/guide.html".to_string(), lastmod("2021-06-01T12:00:00+03:00")),
+        ("https://www.example.com/draft.html".to_string(), LastMod::None),
+        ("https://www.example.com/".to_string(), lastmod("2020-01-01T00:00:00+00:00")),
+    ]);
+}
+
+#[test]
+fn test_lastmod_git_source_root() {
+    let repository = tempfile::tempdir().unwrap();
+    let root = repository.path();
+    git(root, &["init", "-q"], "2020-01-01T00:00:00+00:00");
+    fs::write(root.join(".gitignore"), "public/\n").unwrap();
+    commit(root, &["site/index.html", "site/docs/guide.html"], "2020-01-01T00:00:00+00:00");
+    commit(root, &["site/docs/guide.html"], "2021-06-01T12:00:00+03:00");
+    for file in ["index.html", "docs/guide.html"].iter() {
+        let path = root.join("public").join(file);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, "generated").unwrap();
+    }
+
+    let base_url = Url::parse("https://www.example.com/").unwrap();
+    let history = GitHistory::open(root).unwrap();
+    let options = GeneratorOptions::new().lastmod_provider(history.clone());
+    let entries = generate_from_dir(root.join("public"), &base_url, &options).unwrap();
+    assert!(entries.iter().all(|entry| entry.lastmod == LastMod::None));
+
+    let options = GeneratorOptions::new().lastmod_provider(history.source_root(root.join("public"), root.join("site")));
+    let entries = generate_from_dir(root.join("public"), &base_url, &options).unwrap();
+    let lastmods: Vec<(String, LastMod)> = entries
+        .into_iter()
+        .map(|entry| (entry.loc.to_string(), entry.lastmod))
+        .collect();
+    assert_eq!(lastmods, vec![
+        ("https://www.example.com/docs/guide.html".to_string(), lastmod("2021-06-01T12:00:00+03:00")),
+        ("https://www.example.com/".to_string(), lastmod("2020-01-01T00:00:00+00:00")),
+    ]);
+}
+
+#[test]
+fn test_lastmod_errors() {
+    let directory = tempfile::tempdir().unwrap();
+    assert!(GitHistory::open(directory.path()).is_err());
+    assert!(FileTime.lastmod(&directory.path().join("missing.html")).is_err());
+    fs::write(directory.path().join("index.html"), "").unwrap();
+    assert!(matches!(FileTime.lastmod(&directory.path().join("index.html")).unwrap(), LastMod::DateTime(_)));
+}
@@ -0,0 +1,137 @@
+#![cfg(feature = "output")]
+use sitemap::output::{AtomicFile, publish_paginated};
+use sitemap::provider::{MemoryProvider, SiteMapPaginator, UrlProvider};
+use sitemap::structs::UrlEntry;
+use sitemap::writer::SiteMapWriter;
+use sitemap::{Error, Limit};
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+use url::Url;
+
+fn files(directory: &Path) -> Vec<String> {
+    let mut files: Vec<String> = fs::read_dir(directory)
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+        .collect();
+    files.sort();
+    files
+}
+
+#[test]
+fn test_output_commit() {
+    let directory = tempfile::tempdir().unwrap();
+    let path = directory.path().join("sitemap.xml");
+    fs::write(&path, "previous").unwrap();
+
+    let mut urlwriter = SiteMapWriter::new(AtomicFile::create(&path).unwrap()).start_urlset().unwrap();
+    urlwriter.url("http://www.example.com/").unwrap();
+    assert_eq!(fs::read_to_string(&path).unwrap(), "previous");
+    assert_eq!(files(directory.path()).len(), 2);
+    urlwriter.commit().unwrap();
+
+    let content = fs::read_to_string(&path).unwrap();
+    assert!(content.contains("<loc>http://www.example.com/</loc>"));
+    assert!(content.ends_with("</urlset>"));
+    assert_eq!(files(directory.path()), vec!["sitemap.xml"]);
+
+    let mut index_writer = SiteMapWriter::new(AtomicFile::create(directory.path().join("index.xml")).unwrap())
+        .start_sitemapindex()
+        .unwrap();
+    index_writer.sitemap("http://www.example.com/sitemap.xml").unwrap();
+    index_writer.commit().unwrap();
+    assert!(fs::read_to_string(directory.path().join("index.xml")).unwrap().contains("<sitemap>"));
+
+    AtomicFile::publish(&path, b"replaced").unwrap();
+    assert_eq!(fs::read_to_string(&path).unwrap(), "replaced");
+    assert_eq!(files(directory.path()), vec!["index.xml", "sitemap.xml"]);
+}
+
+#[test]
+fn test_output_cleanup() {
+    let directory = tempfile::tempdir().unwrap();
+    let path = directory.path().join("sitemap.xml");
+    {
+        let mut file = AtomicFile::create(&path).unwrap();
+        file.write_all(b"<urlset>").unwrap();
+        assert_eq!(file.path(), path.as_path());
+    }
+    assert!(files(directory.path()).is_empty());
+
+    let result = (|| -> Result<(), Error> {
+        let mut urlwriter = SiteMapWriter::new(AtomicFile::create(&path)?).start_urlset()?;
+        for index in 0.. {
+            urlwriter.url(UrlEntry::from(format!("http://www.example.com/{}", index)))?;
+        }
+        urlwriter.commit()
+    })();
+    assert!(matches!(result, Err(Error::LimitExceeded(Limit::Entries(_)))));
+    assert!(files(directory.path()).is_empty());
+
+    assert!(matches!(AtomicFile::create(directory.path().join("missing").join("sitemap.xml")), Err(Error::Io(_))));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_output_permissions() {
+    use std::os::unix::fs::PermissionsExt;
+    let directory = tempfile::tempdir().unwrap();
+    let path = directory.path().join("sitemap.xml");
+    AtomicFile::publish(&path, b"").unwrap();
+    let created = directory.path().join("created.xml");
+    fs::File::create(&created).unwrap();
+    assert_eq!(
+        fs::metadata(&path).unwrap().permissions().mode(),
+        fs::metadata(&created).unwrap().permissions().mode(),
+    );
+}
+
+/// Fails to fetch urls past the first page.
+struct Failing;
+
+impl UrlProvider for Failing {
+    fn count(&self) -> Result<usize, Error> {
+        Ok(4)
+    }
+
+    fn fetch(&self, offset: usize, limit: usize) -> Result<Vec<UrlEntry>, Error> {
+        if offset > 0 {
+            return Err(Error::MissingLocation);
+        }
+        Ok((offset..offset + limit).map(|id| UrlEntry::from(format!("https://www.example.com/{}", id))).collect())
+    }
+}
+
+#[test]
+fn test_output_publish_paginated() {
+    let directory = tempfile::tempdir().unwrap();
+    let base_url = Url::parse("https://www.example.com/").unwrap();
+    let provider = MemoryProvider::new((0..3).map(|id| UrlEntry::from(format!("https://www.example.com/{}", id))));
+    publish_paginated(&SiteMapPaginator::new(provider, base_url.clone()).part_size(2), directory.path(), "sitemap.xml").unwrap();
+    assert_eq!(files(directory.path()), vec!["sitemap-1.xml", "sitemap-2.xml", "sitemap.xml"]);
+    let index = fs::read_to_string(directory.path().join("sitemap.xml")).unwrap();
+    assert!(index.contains("<loc>https://www.example.com/sitemap-2.xml</loc>"));
+    assert!(index.ends_with("</sitemapindex>"));
+    assert!(fs::read_to_string(directory.path().join("sitemap-2.xml")).unwrap().contains("<loc>https://www.example.com/2</loc>"));
+
+    let result = publish_paginated(&SiteMapPaginator::new(Failing, base_url).part_size(2), directory.path(), "sitemap.xml");
+    assert!(matches!(result, Err(Error::MissingLocation)));
+    assert_eq!(fs::read_to_string(directory.path().join("sitemap.xml")).unwrap(), index);
+    assert_eq!(files(directory.path()), vec!["sitemap-1.xml", "sitemap-2.xml", "sitemap.xml"]);
+}
+
+#[cfg(feature = "gzip")]
+#[test]
+fn test_output_publish_paginated_gzip() {
+    use std::io::Read;
+    let directory = tempfile::tempdir().unwrap();
+    let provider = MemoryProvider::new(vec![UrlEntry::from("https://www.example.com/")]);
+    let paginator = SiteMapPaginator::new(provider, Url::parse("https://www.example.com/").unwrap()).gzip(true);
+    publish_paginated(&paginator, directory.path(), "sitemap.xml").unwrap();
+    assert_eq!(files(directory.path()), vec!["sitemap-1.xml.gz", "sitemap.xml"]);
+    let mut part = String::new();
+    flate2::read::GzDecoder::new(fs::File::open(directory.path().join("sitemap-1.xml.gz")).unwrap())
+        .read_to_string(&mut part)
+        .unwrap();
+    assert!(part.contains("<loc>https://www.example.com/</loc>"));
+}
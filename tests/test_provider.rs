@@ -0,0 +1,91 @@
+use sitemap::provider::{MemoryProvider, SiteMapPaginator, UrlProvider};
+use sitemap::structs::UrlEntry;
+use sitemap::writer::SiteMapWriter;
+use sitemap::Error;
+use chrono::DateTime;
+use std::cell::RefCell;
+use url::Url;
+
+/// Records queries of the catalogue.
+struct Catalogue {
+    size: usize,
+    queries: RefCell<Vec<(usize, usize)>>,
+}
+
+impl UrlProvider for Catalogue {
+    fn count(&self) -> Result<usize, Error> {
+        Ok(self.size)
+    }
+
+    fn fetch(&self, offset: usize, limit: usize) -> Result<Vec<UrlEntry>, Error> {
+        self.queries.borrow_mut().push((offset, limit));
+        let end = (offset + limit).min(self.size);
+        Ok((offset..end).map(|id| UrlEntry::from(format!("https://www.example.com/products/{}", id))).collect())
+    }
+}
+
+fn write_part<P: UrlProvider>(paginator: &SiteMapPaginator<P>, part: usize) -> String {
+    let mut output = Vec::new();
+    paginator.write_part(part, SiteMapWriter::new(&mut output)).unwrap();
+    String::from_utf8(output).unwrap()
+}
+
+#[test]
+fn test_provider_part() {
+    let catalogue = Catalogue { size: 20_000_000, queries: RefCell::new(Vec::new()) };
+    let paginator = SiteMapPaginator::new(catalogue, Url::parse("https://www.example.com/sitemaps/").unwrap());
+    assert_eq!(paginator.parts().unwrap(), 400);
+    assert_eq!(paginator.part_url(37).unwrap().as_str(), "https://www.example.com/sitemaps/sitemap-37.xml");
+    let part = write_part(&paginator, 37);
+    assert_eq!(paginator.provider().queries.borrow().as_slice(), &[(1_800_000, 50_000)]);
+    assert!(part.contains("<loc>https://www.example.com/products/1800000</loc>"));
+    assert!(part.contains("<loc>https://www.example.com/products/1849999</loc>"));
+    assert!(!part.contains("<loc>https://www.example.com/products/1850000</loc>"));
+}
+
+#[test]
+fn test_provider_index() {
+    let entries = (1..=5).map(|day| {
+        UrlEntry::builder()
+            .loc(format!("https://www.example.com/{}.html", day))
+            .lastmod(DateTime::parse_from_rfc3339(&format!("2020-01-0{}T00:00:00+00:00", day)).unwrap())
+            .build()
+            .unwrap()
+    });
+    let paginator = SiteMapPaginator::new(MemoryProvider::new(entries), Url::parse("https://www.example.com/").unwrap())
+        .part_size(2);
+    assert_eq!(paginator.parts().unwrap(), 3);
+    assert_eq!(paginator.part_lastmod(2).unwrap(), Some(DateTime::parse_from_rfc3339("2020-01-04T00:00:00+00:00").unwrap()));
+    let mut output = Vec::new();
+    let (_, lastmod) = paginator.write_index(SiteMapWriter::new(&mut output)).unwrap();
+    assert_eq!(lastmod, Some(DateTime::parse_from_rfc3339("2020-01-05T00:00:00+00:00").unwrap()));
+    let index = String::from_utf8(output).unwrap();
+    assert!(index.contains("<loc>https://www.example.com/sitemap-1.xml</loc>\n    <lastmod>2020-01-02T00:00:00+00:00</lastmod>"));
+    assert!(index.contains("<loc>https://www.example.com/sitemap-3.xml</loc>\n    <lastmod>2020-01-05T00:00:00+00:00</lastmod>"));
+    assert!(!index.contains("sitemap-4.xml"));
+
+    assert!(write_part(&paginator, 3).contains("<loc>https://www.example.com/5.html</loc>"));
+    for part in [0, 4].iter() {
+        let result = paginator.write_part(*part, SiteMapWriter::new(Vec::new()));
+        assert!(matches!(result, Err(Error::InvalidPart { parts: 3, .. })), "{}", part);
+    }
+    assert!(matches!(paginator.part_lastmod(4), Err(Error::InvalidPart { part: 4, parts: 3 })));
+}
+
+#[test]
+fn test_provider_queries_once() {
+    let catalogue = Catalogue { size: 120_000, queries: RefCell::new(Vec::new()) };
+    let paginator = SiteMapPaginator::new(catalogue, Url::parse("https://www.example.com/").unwrap());
+    let mut output = Vec::new();
+    let (_, lastmod) = paginator.write_index(SiteMapWriter::new(&mut output)).unwrap();
+    assert_eq!(lastmod, None);
+    assert!(paginator.provider().queries.borrow().is_empty());
+    let index = String::from_utf8(output).unwrap();
+    assert!(index.contains("<loc>https://www.example.com/sitemap-3.xml</loc>"));
+    assert!(!index.contains("<lastmod>"));
+
+    let mut output = Vec::new();
+    let (_, lastmod) = paginator.write_part(2, SiteMapWriter::new(&mut output)).unwrap();
+    assert_eq!(lastmod, None);
+    assert_eq!(paginator.provider().queries.borrow().as_slice(), &[(50_000, 50_000)]);
+}
@@ -1,10 +1,11 @@
-use sitemap::reader::{SiteMapReader, SiteMapEntity};
+use sitemap::reader::{SiteMapReader, SiteMapEntity, ReaderLimits};
 use url::Url;
 use std::fs::File;
 use chrono::DateTime;
 use sitemap::structs::{ChangeFreq, SiteMapEntry, UrlEntry};
-use xml::reader;
-fn read_sitemap(file_name: &str) -> (Vec<UrlEntry>, Vec<SiteMapEntry>, Vec<reader::Error>) {
+use sitemap::scope::SiteMapScope;
+use sitemap::{Error, Limit};
+fn read_sitemap(file_name: &str) -> (Vec<UrlEntry>, Vec<SiteMapEntry>, Vec<Error>) {
     let mut urls = Vec::new();
     let mut sitemaps = Vec::new();
     let mut errors = Vec::new();
@@ -21,9 +22,12 @@ fn read_sitemap(file_name: &str) -> (Vec<UrlEntry>, Vec<SiteMapEntry>, Vec<reade
             SiteMapEntity::Err(error) => {
                 errors.push(error);
             }
+            other => {
+                unreachable!("scope and validation are not defined: {:?}", other);
+            }
         }
     }
-    return (urls, sitemaps, errors);
+    (urls, sitemaps, errors)
 }
 #[test]
 fn test_read_sitemap_check_urls() {
@@ -84,3 +88,251 @@ fn test_read_sitemap_check_sitemaps() {
     assert_eq!(sitemaps[1].lastmod.get_time().unwrap(),
                DateTime::parse_from_rfc3339("2005-01-01T00:00:00+00:00").unwrap());
 }
+
+static SCOPED_CONTENT: &str =
+    "<?xml version=\"1.0\" encoding=\"UTF-8\"?>
+<urlset xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\">
+  <url><loc>http://www.example.com/catalog/item1.html</loc></url>
+  <url><loc>http://www.example.com/index.html</loc></url>
+  <url><loc>https://www.example.com/catalog/item2.html</loc></url>
+  <url><loc>http://www.example.org/item3.html</loc></url>
+</urlset>";
+
+fn read_scoped(scope: SiteMapScope) -> (Vec<UrlEntry>, Vec<UrlEntry>) {
+    let mut urls = Vec::new();
+    let mut out_of_scope = Vec::new();
+    let parser = SiteMapReader::new(SCOPED_CONTENT.as_bytes()).with_scope(scope);
+    for entity in parser {
+        match entity {
+            SiteMapEntity::Url(url_entry) => {
+                urls.push(url_entry);
+            }
+            SiteMapEntity::OutOfScope(url_entry) => {
+                out_of_scope.push(url_entry);
+            }
+            other => panic!("unexpected entity {:?}", other),
+        }
+    }
+    (urls, out_of_scope)
+}
+
+#[test]
+fn test_read_sitemap_check_scope() {
+    let scope = SiteMapScope::new(Url::parse("http://www.example.com/catalog/sitemap.xml").unwrap());
+    let (urls, out_of_scope) = read_scoped(scope);
+    assert_eq!(urls.len(), 1);
+    assert_eq!(urls[0].loc.get_url().unwrap(),
+               Url::parse("http://www.example.com/catalog/item1.html").unwrap());
+    assert_eq!(out_of_scope.len(), 3);
+
+    let scope = SiteMapScope::new(Url::parse("http://www.example.com/sitemap.xml").unwrap())
+        .cross_submit(Url::parse("http://www.example.org/robots.txt").unwrap());
+    let (urls, out_of_scope) = read_scoped(scope);
+    assert_eq!(urls.len(), 3);
+    assert_eq!(out_of_scope.len(), 1);
+    assert_eq!(out_of_scope[0].loc.get_url().unwrap(),
+               Url::parse("https://www.example.com/catalog/item2.html").unwrap());
+}
+
+#[test]
+fn test_read_sitemap_syntax_error() {
+    let content = "<?xml version=\"1.0\" encoding=\"UTF-8\"?>
+<urlset xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\">
+  <url><loc>http://www.example.com/</loc></url>
+  <url><loc>http://www.example.com/other</url>
+</urlset>";
+    let entities: Vec<SiteMapEntity> = SiteMapReader::new(content.as_bytes()).collect();
+    assert!(matches!(entities[0], SiteMapEntity::Url(_)));
+    match entities.last() {
+        Some(SiteMapEntity::Err(Error::XmlSyntax { line, .. })) => assert_eq!(*line, 4),
+        other => panic!("expected syntax error, got {:?}", other),
+    }
+}
+
+static INVALID_CONTENT: &str =
+    "<?xml version=\"1.0\" encoding=\"UTF-8\"?>
+<urlset xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\">
+  <url>
+    <loc>http://www.example.com/valid.html</loc>
+    <priority>0.5</priority>
+  </url>
+  <url>
+    <loc>http://www.example.com/invalid.html</loc>
+    <lastmod>yesterday</lastmod>
+    <changefreq>sometimes</changefreq>
+    <priority>1.5</priority>
+  </url>
+  <url>
+    <priority>0.1</priority>
+  </url>
+  <url>
+    <loc>http://www.example.org/other.html</loc>
+  </url>
+</urlset>";
+
+#[test]
+fn test_read_sitemap_validation() {
+    let scope = SiteMapScope::new(Url::parse("http://www.example.com/sitemap.xml").unwrap());
+    let parser = SiteMapReader::new(INVALID_CONTENT.as_bytes()).with_scope(scope).with_validation();
+    let entities: Vec<SiteMapEntity> = parser.collect();
+    assert_eq!(entities.len(), 4);
+    match &entities[0] {
+        SiteMapEntity::Url(entry) => {
+            assert_eq!(entry.loc.get_url().unwrap().as_str(), "http://www.example.com/valid.html");
+        }
+        other => panic!("expected valid url, got {:?}", other),
+    }
+    match &entities[1] {
+        SiteMapEntity::InvalidUrl { entry, problems } => {
+            assert_eq!(entry.loc.get_url().unwrap().as_str(), "http://www.example.com/invalid.html");
+            assert_eq!(problems.len(), 3);
+            assert!(matches!(&problems[0], Error::InvalidLastMod { value, .. } if value == "yesterday"));
+            assert!(matches!(&problems[1], Error::InvalidChangeFreq { value } if value == "sometimes"));
+            assert!(matches!(&problems[2], Error::InvalidPriority { value } if value == "1.5"));
+        }
+        other => panic!("expected invalid url, got {:?}", other),
+    }
+    match &entities[2] {
+        SiteMapEntity::InvalidUrl { problems, .. } => {
+            assert_eq!(problems.len(), 1);
+            assert!(matches!(problems[0], Error::MissingLocation));
+        }
+        other => panic!("expected invalid url, got {:?}", other),
+    }
+    match &entities[3] {
+        SiteMapEntity::InvalidUrl { problems, .. } => {
+            assert_eq!(problems.len(), 1);
+            assert!(matches!(problems[0], Error::ScopeViolation(_)));
+        }
+        other => panic!("expected invalid url, got {:?}", other),
+    }
+
+    let entities: Vec<SiteMapEntity> = SiteMapReader::new(INVALID_CONTENT.as_bytes()).collect();
+    assert!(entities.iter().all(|entity| matches!(entity, SiteMapEntity::Url(_))));
+}
+
+#[test]
+fn test_read_sitemap_extension() {
+    let content = "<urlset xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\"
+        xmlns:image=\"http://www.google.com/schemas/sitemap-image/1.1\">
+  <url>
+    <loc>http://www.example.com/</loc>
+    <image:image><image:loc>http://www.example.com/1.png</image:loc></image:image>
+    <image:image><image:loc>http://www.example.com/2.png</image:loc></image:image>
+  </url>
+</urlset>";
+    let entities: Vec<SiteMapEntity> = SiteMapReader::new(content.as_bytes()).with_validation().collect();
+    match &entities[..] {
+        [SiteMapEntity::InvalidUrl { entry, problems }] => {
+            assert_eq!(entry.loc.get_url().unwrap().as_str(), "http://www.example.com/");
+            match &problems[..] {
+                [Error::UnsupportedExtension { namespace }] => {
+                    assert_eq!(namespace, "http://www.google.com/schemas/sitemap-image/1.1");
+                }
+                other => panic!("expected unsupported extension, got {:?}", other),
+            }
+        }
+        other => panic!("expected invalid url, got {:?}", other),
+    }
+
+    let entities: Vec<SiteMapEntity> = SiteMapReader::new(content.as_bytes()).collect();
+    assert!(matches!(entities[..], [SiteMapEntity::Url(_)]));
+
+    let legacy = "<urlset xmlns=\"http://www.google.com/schemas/sitemap/0.84\">
+  <url><loc>http://www.example.com/</loc><lastmod>2005-01-01</lastmod></url>
+</urlset>";
+    let entities: Vec<SiteMapEntity> = SiteMapReader::new(legacy.as_bytes()).with_validation().collect();
+    assert!(matches!(entities[..], [SiteMapEntity::Url(_)]), "{:?}", entities);
+}
+
+#[test]
+fn test_read_sitemap_cdata() {
+    let content = "<urlset>
+  <url><loc><![CDATA[http://www.example.com/?a=1&b=2]]></loc><changefreq>daily</changefreq></url>
+</urlset>";
+    let entities: Vec<SiteMapEntity> = SiteMapReader::new(content.as_bytes()).collect();
+    assert_eq!(entities.len(), 1);
+    match &entities[0] {
+        SiteMapEntity::Url(entry) => {
+            assert_eq!(entry.loc.get_url().unwrap().as_str(), "http://www.example.com/?a=1&b=2");
+            assert_eq!(entry.changefreq, ChangeFreq::Daily);
+        }
+        other => panic!("expected url, got {:?}", other),
+    }
+
+    let content = "<urlset><url><loc>http://www.example.com/<![CDATA[a&b]]>?c=<![CDATA[d]]>e</loc></url></urlset>";
+    let entities: Vec<SiteMapEntity> = SiteMapReader::new(content.as_bytes()).collect();
+    match &entities[..] {
+        [SiteMapEntity::Url(entry)] => {
+            assert_eq!(entry.loc.get_url().unwrap().as_str(), "http://www.example.com/a&b?c=de");
+        }
+        other => panic!("expected url, got {:?}", other),
+    }
+}
+
+fn read_limited(content: &str, limits: ReaderLimits) -> Vec<SiteMapEntity> {
+    SiteMapReader::new(content.as_bytes()).with_limits(limits).collect()
+}
+
+fn limit_exceeded(entities: &[SiteMapEntity]) -> Option<Limit> {
+    match entities.last() {
+        Some(SiteMapEntity::Err(Error::LimitExceeded(limit))) => Some(*limit),
+        _ => None,
+    }
+}
+
+#[test]
+fn test_read_sitemap_limits() {
+    let content = "<?xml version=\"1.0\" encoding=\"UTF-8\"?>
+<urlset xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\">
+  <url><loc>http://www.example.com/</loc></url>
+  <url><loc>http://www.example.com/other</loc></url>
+  <url><loc>http://www.example.com/third</loc></url>
+</urlset>";
+    assert_eq!(read_limited(content, ReaderLimits::new()).len(), 3);
+    assert_eq!(read_limited(content, ReaderLimits::unlimited()).len(), 3);
+
+    let entities = read_limited(content, ReaderLimits::new().max_entries(2));
+    assert_eq!(entities.len(), 3);
+    assert_eq!(limit_exceeded(&entities), Some(Limit::Entries(2)));
+
+    let entities = read_limited(content, ReaderLimits::new().max_bytes(200));
+    assert_eq!(entities.len(), 3);
+    assert_eq!(limit_exceeded(&entities), Some(Limit::Bytes(200)));
+
+    let entities = read_limited(content, ReaderLimits::new().max_field_length(24));
+    assert_eq!(entities.len(), 2);
+    assert_eq!(limit_exceeded(&entities), Some(Limit::FieldLength(24)));
+
+    let entities = read_limited(content, ReaderLimits::new().max_depth(2));
+    assert_eq!(limit_exceeded(&entities), Some(Limit::Depth(2)));
+
+    let content = "<urlset xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\"><url a=\"1\" b=\"2\"/></urlset>";
+    assert_eq!(read_limited(content, ReaderLimits::new().max_attributes(2)).len(), 1);
+    let entities = read_limited(content, ReaderLimits::new().max_attributes(1));
+    assert_eq!(limit_exceeded(&entities), Some(Limit::Attributes(1)));
+
+    let content = format!("<urlset>{}</urlset>", "<a>".repeat(1000));
+    let entities = read_limited(&content, ReaderLimits::new());
+    assert_eq!(entities.len(), 1);
+    assert_eq!(limit_exceeded(&entities), Some(Limit::Depth(32)));
+}
+
+#[test]
+fn test_read_sitemap_entity_expansion() {
+    let content = "<?xml version=\"1.0\"?>
+<!DOCTYPE urlset [
+  <!ENTITY a \"aaaaaaaaaa\">
+  <!ENTITY b \"&a;&a;&a;&a;&a;&a;&a;&a;&a;&a;\">
+  <!ENTITY c \"&b;&b;&b;&b;&b;&b;&b;&b;&b;&b;\">
+  <!ENTITY d \"&c;&c;&c;&c;&c;&c;&c;&c;&c;&c;\">
+]>
+<urlset><url><loc>http://www.example.com/&d;&d;&d;&d;</loc></url></urlset>";
+    let entities: Vec<SiteMapEntity> = SiteMapReader::new(content.as_bytes()).collect();
+    assert_eq!(entities.len(), 1);
+    assert!(matches!(entities[0], SiteMapEntity::Err(Error::XmlSyntax { .. })), "{:?}", entities);
+
+    let content = "<!-- <!DOCTYPE> --><?xml-stylesheet href=\"a?b\"?><urlset><url><loc>http://www.example.com/</loc></url></urlset>";
+    let entities: Vec<SiteMapEntity> = SiteMapReader::new(content.as_bytes()).collect();
+    assert!(matches!(entities[..], [SiteMapEntity::Url(_)]), "{:?}", entities);
+}
@@ -0,0 +1,83 @@
+use sitemap::schedule::{CrawlEntry, Due, Schedule};
+use sitemap::structs::{ChangeFreq, UrlEntry};
+use chrono::{DateTime, Duration, FixedOffset};
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+fn time(time: &str) -> DateTime<FixedOffset> {
+    DateTime::parse_from_rfc3339(time).unwrap()
+}
+
+fn entry(loc: &str, changefreq: ChangeFreq) -> UrlEntry {
+    UrlEntry::builder().loc(loc).changefreq(changefreq).build().unwrap()
+}
+
+#[test]
+fn test_interval() {
+    assert_eq!(ChangeFreq::Always.interval(), Some(Duration::zero()));
+    assert_eq!(ChangeFreq::Hourly.interval(), Some(Duration::hours(1)));
+    assert_eq!(ChangeFreq::Weekly.interval(), Some(Duration::days(7)));
+    assert_eq!(ChangeFreq::Yearly.interval(), Some(Duration::days(365)));
+    assert_eq!(ChangeFreq::Never.interval(), None);
+    assert_eq!(ChangeFreq::None.interval(), None);
+}
+
+#[test]
+fn test_next_due() {
+    let crawled = time("2020-01-01T00:00:00+00:00");
+    let schedule = Schedule::new();
+    let daily = entry("http://www.example.com/", ChangeFreq::Daily);
+    assert_eq!(schedule.next_due(&daily, None), Due::Immediately);
+    assert_eq!(schedule.next_due(&daily, Some(crawled)), Due::At(time("2020-01-02T00:00:00+00:00")));
+
+    let modified = UrlEntry::builder()
+        .loc("http://www.example.com/")
+        .changefreq(ChangeFreq::Yearly)
+        .lastmod(time("2020-01-01T12:00:00+00:00"))
+        .build()
+        .unwrap();
+    assert_eq!(schedule.next_due(&modified, Some(crawled)), Due::At(time("2020-01-01T12:00:00+00:00")));
+    assert_eq!(
+        schedule.next_due(&modified, Some(time("2020-01-02T00:00:00+00:00"))),
+        Due::At(time("2021-01-01T00:00:00+00:00")),
+    );
+
+    let archived = entry("http://www.example.com/", ChangeFreq::Never);
+    assert_eq!(schedule.next_due(&archived, Some(crawled)), Due::Never);
+    assert!(!Due::Never.is_due(crawled));
+
+    let unknown = UrlEntry::from("http://www.example.com/");
+    let schedule = schedule.default_interval(Duration::hours(6));
+    let due = schedule.next_due(&unknown, Some(crawled));
+    assert_eq!(due, Due::At(time("2020-01-01T06:00:00+00:00")));
+    assert!(!due.is_due(time("2020-01-01T05:59:59+00:00")));
+    assert!(due.is_due(time("2020-01-01T06:00:00+00:00")));
+}
+
+#[test]
+fn test_urgency() {
+    let crawled = time("2020-01-01T00:00:00+00:00");
+    let schedule = Schedule::new();
+    let important = UrlEntry::builder()
+        .loc("http://www.example.com/important")
+        .changefreq(ChangeFreq::Daily)
+        .priority(1.0)
+        .build()
+        .unwrap();
+    let mut heap = BinaryHeap::new();
+    heap.push(Reverse(schedule.entry(entry("http://www.example.com/archive", ChangeFreq::Never), Some(crawled))));
+    heap.push(Reverse(schedule.entry(entry("http://www.example.com/weekly", ChangeFreq::Weekly), Some(crawled))));
+    heap.push(Reverse(schedule.entry(entry("http://www.example.com/daily", ChangeFreq::Daily), Some(crawled))));
+    heap.push(Reverse(schedule.entry(important, Some(crawled))));
+    heap.push(Reverse(schedule.entry(UrlEntry::from("http://www.example.com/new"), None)));
+    let order: Vec<String> = std::iter::from_fn(|| heap.pop())
+        .map(|Reverse(item): Reverse<CrawlEntry>| item.entry.loc.to_string())
+        .collect();
+    assert_eq!(order, vec![
+        "http://www.example.com/new",
+        "http://www.example.com/important",
+        "http://www.example.com/daily",
+        "http://www.example.com/weekly",
+        "http://www.example.com/archive",
+    ]);
+}
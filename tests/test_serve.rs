@@ -0,0 +1,162 @@
+#![cfg(feature = "axum")]
+use sitemap::provider::UrlProvider;
+use sitemap::serve::{ServeError, SiteMapService};
+use sitemap::structs::UrlEntry;
+use sitemap::Error;
+use axum::body::Body;
+use axum::http::{header, Request, StatusCode};
+use axum::response::Response;
+use chrono::DateTime;
+use flate2::read::GzDecoder;
+use std::io::Read;
+use std::sync::{Arc, Mutex};
+use std::thread::{self, ThreadId};
+use tower::ServiceExt;
+use url::Url;
+
+fn service() -> SiteMapService {
+    let entries = (1..=5).map(|index| {
+        let lastmod = DateTime::parse_from_rfc3339(&format!("2020-01-0{}T00:00:00+03:00", index)).unwrap();
+        UrlEntry::builder()
+            .loc(format!("https://www.example.com/{}.html", index))
+            .lastmod(lastmod)
+            .build()
+            .unwrap()
+    });
+    SiteMapService::new(Url::parse("https://www.example.com/").unwrap(), entries).part_size(2)
+}
+
+async fn get(service: SiteMapService, uri: &str, headers: &[(header::HeaderName, &str)]) -> Response {
+    let mut request = Request::builder().uri(uri);
+    for (name, value) in headers {
+        request = request.header(name, *value);
+    }
+    service.router().oneshot(request.body(Body::empty()).unwrap()).await.unwrap()
+}
+
+async fn read_body(response: Response) -> Vec<u8> {
+    axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap().to_vec()
+}
+
+fn gunzip(body: &[u8]) -> String {
+    let mut result = String::new();
+    GzDecoder::new(body).read_to_string(&mut result).unwrap();
+    result
+}
+
+#[tokio::test]
+async fn test_serve_index() {
+    assert_eq!(service().parts().unwrap(), 3);
+    let response = get(service(), "/sitemap.xml", &[]).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()[header::CONTENT_TYPE], "application/xml; charset=utf-8");
+    assert_eq!(response.headers()[header::LAST_MODIFIED], "Sat, 04 Jan 2020 21:00:00 GMT");
+    assert!(response.headers().get(header::CONTENT_ENCODING).is_none());
+    let body = String::from_utf8(read_body(response).await).unwrap();
+    assert!(body.contains("<loc>https://www.example.com/sitemap-1.xml</loc>"));
+    assert!(body.contains("<lastmod>2020-01-02T00:00:00+03:00</lastmod>"));
+    assert!(body.contains("<loc>https://www.example.com/sitemap-3.xml</loc>"));
+    assert!(!body.contains("sitemap-4.xml"));
+
+    let response = get(service().gzip(true), "/sitemap.xml", &[]).await;
+    let body = String::from_utf8(read_body(response).await).unwrap();
+    assert!(body.contains("<loc>https://www.example.com/sitemap-1.xml.gz</loc>"));
+}
+
+#[tokio::test]
+async fn test_serve_parts() {
+    let response = get(service(), "/sitemap-2.xml", &[]).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()[header::LAST_MODIFIED], "Fri, 03 Jan 2020 21:00:00 GMT");
+    let body = String::from_utf8(read_body(response).await).unwrap();
+    assert!(body.contains("<loc>https://www.example.com/3.html</loc>"));
+    assert!(body.contains("<loc>https://www.example.com/4.html</loc>"));
+    assert!(!body.contains("<loc>https://www.example.com/5.html</loc>"));
+
+    let response = get(service(), "/sitemap-3.xml.gz", &[]).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()[header::CONTENT_TYPE], "application/gzip");
+    assert!(response.headers().get(header::CONTENT_ENCODING).is_none());
+    assert!(gunzip(&read_body(response).await).contains("<loc>https://www.example.com/5.html</loc>"));
+
+    let response = get(service(), "/sitemap-3.xml", &[(header::ACCEPT_ENCODING, "deflate, gzip;q=0.5")]).await;
+    assert_eq!(response.headers()[header::CONTENT_ENCODING], "gzip");
+    assert_eq!(response.headers()[header::CONTENT_TYPE], "application/xml; charset=utf-8");
+    assert!(gunzip(&read_body(response).await).contains("<loc>https://www.example.com/5.html</loc>"));
+
+    let response = get(service(), "/sitemap-3.xml", &[(header::ACCEPT_ENCODING, "gzip;q=0")]).await;
+    assert!(response.headers().get(header::CONTENT_ENCODING).is_none());
+
+    for uri in ["/sitemap-0.xml", "/sitemap-4.xml", "/sitemap-01.xml", "/robots.txt", "/sitemap-1.xml.br"].iter() {
+        assert_eq!(get(service(), uri, &[]).await.status(), StatusCode::NOT_FOUND, "{}", uri);
+    }
+}
+
+#[tokio::test]
+async fn test_serve_etag() {
+    let response = get(service(), "/sitemap-1.xml", &[]).await;
+    let etag = response.headers()[header::ETAG].to_str().unwrap().to_string();
+    assert_eq!(get(service(), "/sitemap-1.xml", &[]).await.headers()[header::ETAG], etag.as_str());
+    assert_ne!(get(service(), "/sitemap-2.xml", &[]).await.headers()[header::ETAG], etag.as_str());
+    assert_ne!(get(service(), "/sitemap-1.xml.gz", &[]).await.headers()[header::ETAG], etag.as_str());
+
+    let response = get(service(), "/sitemap-1.xml", &[(header::IF_NONE_MATCH, &etag)]).await;
+    assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+    assert_eq!(response.headers()[header::ETAG], etag.as_str());
+    assert!(read_body(response).await.is_empty());
+
+    let weak = format!("\"other\", W/{}", etag);
+    let response = get(service(), "/sitemap-1.xml", &[(header::IF_NONE_MATCH, &weak)]).await;
+    assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+
+    let response = get(service(), "/sitemap-1.xml", &[(header::IF_NONE_MATCH, "\"other\"")]).await;
+    assert_eq!(response.status(), StatusCode::OK);
+}
+
+/// Records threads querying the urls.
+struct Threads(Arc<Mutex<Vec<ThreadId>>>);
+
+impl UrlProvider for Threads {
+    fn count(&self) -> Result<usize, Error> {
+        self.0.lock().unwrap().push(thread::current().id());
+        Ok(1)
+    }
+
+    fn fetch(&self, _offset: usize, _limit: usize) -> Result<Vec<UrlEntry>, Error> {
+        self.0.lock().unwrap().push(thread::current().id());
+        Ok(vec![UrlEntry::from("https://www.example.com/")])
+    }
+}
+
+#[tokio::test]
+async fn test_serve_blocking_provider() {
+    let threads = Arc::new(Mutex::new(Vec::new()));
+    let service = SiteMapService::with_provider(Url::parse("https://www.example.com/").unwrap(), Threads(threads.clone()));
+    assert_eq!(get(service, "/sitemap-1.xml", &[]).await.status(), StatusCode::OK);
+    let threads = threads.lock().unwrap();
+    assert!(!threads.is_empty());
+    assert!(threads.iter().all(|id| *id != thread::current().id()));
+}
+
+/// Fails every query.
+struct Broken;
+
+impl UrlProvider for Broken {
+    fn count(&self) -> Result<usize, Error> {
+        Err(Error::MissingLocation)
+    }
+
+    fn fetch(&self, _offset: usize, _limit: usize) -> Result<Vec<UrlEntry>, Error> {
+        Err(Error::MissingLocation)
+    }
+}
+
+#[tokio::test]
+async fn test_serve_error() {
+    let service = SiteMapService::with_provider(Url::parse("https://www.example.com/").unwrap(), Broken);
+    let response = get(service, "/sitemap.xml", &[]).await;
+    assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    let error = response.extensions().get::<ServeError>().unwrap().clone();
+    assert!(matches!(*error.0, Error::MissingLocation));
+    assert!(read_body(response).await.is_empty());
+}
@@ -0,0 +1,159 @@
+use sitemap::structs::{ChangeFreq, LastMod, Location, Priority, PriorityValue, SiteMapEntry, UrlEntry};
+use sitemap::Error;
+use chrono::DateTime;
+use std::collections::HashSet;
+
+fn hundredths(priority: &str) -> Option<u8> {
+    Priority::from(priority.to_string()).get_value().map(|value| value.hundredths())
+}
+
+#[test]
+fn test_priority_parse() {
+    assert_eq!(hundredths("0.8"), Some(80));
+    assert_eq!(hundredths("0.85"), Some(85));
+    assert_eq!(hundredths(" .5 "), Some(50));
+    assert_eq!(hundredths("1"), Some(100));
+    assert_eq!(hundredths("1.000"), Some(100));
+    assert_eq!(hundredths("0"), Some(0));
+    assert_eq!(hundredths("-0.0"), Some(0));
+    assert_eq!(hundredths("+0.3"), Some(30));
+    // Rounding half up to hundredths.
+    assert_eq!(hundredths("0.125"), Some(13));
+    assert_eq!(hundredths("0.1249"), Some(12));
+    assert_eq!(hundredths("0.995"), Some(100));
+
+    assert!(matches!(Priority::from("1.5".to_string()), Priority::ErrValueGreaterOne(ref value) if value == "1.5"));
+    assert!(matches!(Priority::from("1.001".to_string()), Priority::ErrValueGreaterOne(_)));
+    assert!(matches!(Priority::from("10".to_string()), Priority::ErrValueGreaterOne(_)));
+    assert!(matches!(Priority::from("-0.1".to_string()), Priority::ErrValueLesserZero(_)));
+    assert!(matches!(Priority::from("".to_string()), Priority::ParseErr(_)));
+    assert!(matches!(Priority::from(".".to_string()), Priority::ParseErr(_)));
+    assert!(matches!(Priority::from("1e-1".to_string()), Priority::ParseErr(_)));
+    assert!(matches!(Priority::from("high".to_string()), Priority::ParseErr(_)));
+}
+
+#[test]
+fn test_priority_display() {
+    let display = |hundredths: u8| PriorityValue::from_hundredths(hundredths).unwrap().to_string();
+    assert_eq!(display(0), "0.0");
+    assert_eq!(display(5), "0.05");
+    assert_eq!(display(30), "0.3");
+    assert_eq!(display(35), "0.35");
+    assert_eq!(display(100), "1.0");
+    assert!(PriorityValue::from_hundredths(101).is_none());
+    assert_eq!(PriorityValue::from_tenths(7), PriorityValue::from_hundredths(70));
+}
+
+#[test]
+fn test_priority_from_float() {
+    assert_eq!(Priority::from(0.1f32 + 0.2f32).get_value(), PriorityValue::from_hundredths(30));
+    assert_eq!(Priority::from(0.7f32).get_value(), PriorityValue::from_hundredths(70));
+    assert_eq!(Priority::from(0.285f32).get_value(), PriorityValue::from_hundredths(29));
+    assert!(matches!(Priority::from(f32::NAN), Priority::ParseErr(_)));
+    let entry = UrlEntry::builder()
+        .loc("http://www.example.com/")
+        .priority(0.1f32 * 3.0)
+        .build()
+        .unwrap();
+    assert_eq!(entry.priority.get_value().unwrap().to_string(), "0.3");
+}
+
+#[test]
+fn test_priority_ord() {
+    let low = Priority::from("0.25".to_string());
+    let high = Priority::from("0.5".to_string());
+    assert!(low < high);
+    assert_eq!(Priority::from("0.50".to_string()), high);
+    assert!(PriorityValue::MIN < PriorityValue::DEFAULT && PriorityValue::DEFAULT < PriorityValue::MAX);
+}
+
+#[test]
+fn test_entries_hash_set() {
+    let mut set = HashSet::new();
+    assert!(set.insert(UrlEntry::from("http://www.example.com/")));
+    assert!(!set.insert(UrlEntry::from("http://www.example.com/")));
+    assert!(set.insert(UrlEntry::builder().loc("http://www.example.com/").changefreq(ChangeFreq::Daily).build().unwrap()));
+    assert!(set.insert(UrlEntry {
+        loc: Location::from("not a url".to_string()),
+        lastmod: LastMod::from("yesterday".to_string()),
+        changefreq: ChangeFreq::from("sometimes".to_string()),
+        priority: Priority::from("2".to_string()),
+    }));
+    assert!(!set.insert(UrlEntry {
+        loc: Location::from("not a url".to_string()),
+        lastmod: LastMod::from("yesterday".to_string()),
+        changefreq: ChangeFreq::from("sometimes".to_string()),
+        priority: Priority::from("2".to_string()),
+    }));
+    assert_eq!(set.len(), 3);
+
+    let sitemaps: HashSet<SiteMapEntry> = vec!["http://www.example.com/1.xml", "http://www.example.com/1.xml"]
+        .into_iter()
+        .map(SiteMapEntry::from)
+        .collect();
+    assert_eq!(sitemaps.len(), 1);
+}
+
+#[test]
+fn test_entries_sort() {
+    let mut entries = [
+        UrlEntry::from("http://www.example.com/b"),
+        UrlEntry::from("http://www.example.com/a"),
+        UrlEntry::new(),
+    ];
+    entries.sort();
+    assert!(entries[0].loc.is_none());
+    assert_eq!(entries[1].loc.get_url().unwrap().as_str(), "http://www.example.com/a");
+    assert_eq!(entries[2].loc.get_url().unwrap().as_str(), "http://www.example.com/b");
+
+    let time = |time: &str| LastMod::from(time.to_string());
+    let mut times = [time("2005-01-01"), time("2004-12-23T18:00:15+00:00"), time("invalid"), LastMod::None];
+    times.sort();
+    assert_eq!(times[0], LastMod::None);
+    assert_eq!(times[1], time("2004-12-23T18:00:15+00:00"));
+    assert_eq!(times[2], time("2005-01-01"));
+    assert!(matches!(times[3], LastMod::ParseErr(_)));
+    // Same instant with different offsets.
+    assert_eq!(time("2005-01-01T01:00:00+01:00"), time("2005-01-01T00:00:00Z"));
+    assert_eq!(
+        LastMod::DateTime(DateTime::parse_from_rfc3339("2005-01-01T00:00:00+00:00").unwrap()),
+        time("2005-01-01"),
+    );
+
+    assert!(ChangeFreq::Always < ChangeFreq::Hourly);
+    assert!(ChangeFreq::Yearly < ChangeFreq::Never);
+}
+
+#[test]
+fn test_display_from_str() {
+    for changefreq in ChangeFreq::ALL.iter() {
+        assert_eq!(&changefreq.to_string().parse::<ChangeFreq>().unwrap(), changefreq);
+    }
+    assert_eq!("Weekly".parse::<ChangeFreq>().unwrap(), ChangeFreq::Weekly);
+    assert_eq!(ChangeFreq::Never.to_string(), "never");
+    assert_eq!(ChangeFreq::None.to_string(), "");
+    assert!(matches!("sometimes".parse::<ChangeFreq>(), Err(Error::InvalidChangeFreq { ref value }) if value == "sometimes"));
+    assert!("".parse::<ChangeFreq>().is_err());
+
+    assert_eq!("0.50".parse::<Priority>().unwrap(), Priority::Value(PriorityValue::DEFAULT));
+    assert_eq!("0.25".parse::<PriorityValue>().unwrap().to_string(), "0.25");
+    assert_eq!(Priority::from("1.5".to_string()).to_string(), "Priority '1.5' should be between 0 and 1");
+    assert_eq!(Priority::from("high".to_string()).to_string(), "Not a decimal number 'high'");
+    assert_eq!(ChangeFreq::from("sometimes".to_string()).to_string(), "Not recognized string 'sometimes'");
+    assert_eq!(Location::from("not a url".to_string()).to_string(), "relative URL without a base");
+    assert_eq!(Priority::None.to_string(), "");
+    assert!(matches!("1.5".parse::<Priority>(), Err(Error::InvalidPriority { ref value }) if value == "1.5"));
+    assert!(matches!("high".parse::<PriorityValue>(), Err(Error::InvalidPriority { .. })));
+
+    let lastmod = "2004-12-23T18:00:15+00:00".parse::<LastMod>().unwrap();
+    assert_eq!(lastmod.to_string(), "2004-12-23T18:00:15+00:00");
+    assert_eq!(lastmod.to_string().parse::<LastMod>().unwrap(), lastmod);
+    assert_eq!(LastMod::None.to_string(), "");
+    assert!(matches!("yesterday".parse::<LastMod>(), Err(Error::InvalidLastMod { ref value, .. }) if value == "yesterday"));
+
+    let location = "http://www.example.com/".parse::<Location>().unwrap();
+    assert_eq!(location.to_string(), "http://www.example.com/");
+    assert_eq!(location.to_string().parse::<Location>().unwrap(), location);
+    assert_eq!(Location::None.to_string(), "");
+    assert!(matches!("not a url".parse::<Location>(), Err(Error::InvalidUrl { ref value, .. }) if value == "not a url"));
+}
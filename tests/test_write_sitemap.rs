@@ -1,9 +1,13 @@
-use sitemap::writer::SiteMapWriter;
+use sitemap::reader::{SiteMapReader, SiteMapEntity};
+use sitemap::writer::{SiteMapWriter, SiteMapSequence, IriPolicy, SiteMapWriterConfig, WriteSummary, XmlVersion, MAX_ENTRIES, MAX_BYTES};
 use sitemap::structs::{UrlEntry, ChangeFreq, SiteMapEntry};
-use chrono::{DateTime, NaiveDate, FixedOffset};
+use sitemap::scope::SiteMapScope;
+use sitemap::{Error, Limit};
+use chrono::DateTime;
+use url::Url;
 
-static CONTENT: &str =
-    "<?xml version=\"1.0\" encoding=\"utf-8\"?>
+static URLSET_CONTENT: &str =
+    "<?xml version=\"1.0\" encoding=\"UTF-8\"?>
 <urlset xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\">
   <url>
     \
@@ -22,7 +26,10 @@ static CONTENT: &str =
     \
      <priority>0.1</priority>
   </url>
-</urlset>
+</urlset>";
+
+static INDEX_CONTENT: &str =
+    "<?xml version=\"1.0\" encoding=\"UTF-8\"?>
 <sitemapindex xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\">
   <sitemap>
     \
@@ -35,12 +42,11 @@ static CONTENT: &str =
 
 #[test]
 fn test_write_sitemap() {
-    let mut output = Vec::<u8>::new();
+    let mut sequence = SiteMapSequence::new(|_| Ok(Vec::<u8>::new()));
     {
-        let sitemap_writer = SiteMapWriter::new(&mut output);
+        let sitemap_writer = sequence.next_document().expect("create document");
         let mut urlwriter = sitemap_writer.start_urlset().expect("Can't write the file");
-        let date = DateTime::from_utc(NaiveDate::from_ymd(2016, 7, 8).and_hms(9, 10, 11),
-                                      FixedOffset::east(0));
+        let date = DateTime::parse_from_rfc3339("2016-07-08T09:10:11+00:00").unwrap();
         let url_entry = UrlEntry::builder()
             .loc("http://www.example.com/index.html")
             .changefreq(ChangeFreq::Daily)
@@ -49,8 +55,7 @@ fn test_write_sitemap() {
             .build()
             .expect("valid");
         urlwriter.url(url_entry).expect("Can't write the file");
-        let date1 = DateTime::from_utc(NaiveDate::from_ymd(2016, 7, 18).and_hms(9, 10, 11),
-                                       FixedOffset::east(0));
+        let date1 = DateTime::parse_from_rfc3339("2016-07-18T09:10:11+00:00").unwrap();
         let url_entry = UrlEntry::builder()
             .loc("http://www.example.com/other.html")
             .changefreq(ChangeFreq::Monthly)
@@ -59,8 +64,10 @@ fn test_write_sitemap() {
             .build()
             .expect("valid");
         urlwriter.url(url_entry).expect("Can't write the file");
-        let sitemap_writer = urlwriter.end().expect("close the urlset block");
+        let output = urlwriter.finish().expect("close the urlset block");
+        assert_eq!(std::str::from_utf8(&output), Ok(URLSET_CONTENT));
 
+        let sitemap_writer = sequence.next_document().expect("create document");
         let mut sitemap_index_writer = sitemap_writer.start_sitemapindex()
             .expect("start sitemap index tag");
         let sitemap_entry = SiteMapEntry::builder()
@@ -69,9 +76,51 @@ fn test_write_sitemap() {
             .build()
             .expect("valid");
         sitemap_index_writer.sitemap(sitemap_entry).expect("Can't write the file");
-        sitemap_index_writer.end().expect("close sitemap block");
+        let output = sitemap_index_writer.finish().expect("close sitemap block");
+        assert_eq!(std::str::from_utf8(&output), Ok(INDEX_CONTENT));
     }
-    assert_eq!(std::str::from_utf8(&output),  std::str::from_utf8(CONTENT.as_bytes()));
+    assert_eq!(sequence.count(), 2);
+}
+
+#[test]
+fn test_write_sitemap_sequence() {
+    let scope = SiteMapScope::new(Url::parse("http://www.example.com/").unwrap());
+    let config = SiteMapWriterConfig::new().xml_declaration(false).compact();
+    let mut names = Vec::new();
+    let mut sequence = SiteMapSequence::with_config(|number| {
+        names.push(format!("sitemap-{}.xml", number + 1));
+        Ok(Vec::<u8>::new())
+    }, config).with_scope(scope);
+    let mut urlwriter = sequence.next_document().unwrap().start_urlset_without_ns().unwrap();
+    assert!(matches!(urlwriter.url("http://other.com/"), Err(Error::ScopeViolation(_))));
+    urlwriter.url("http://www.example.com/").unwrap();
+    let first = urlwriter.finish().unwrap();
+    let (second, summary) = sequence.next_document().unwrap()
+        .write_urlset_from_iter(["http://www.example.com/a"])
+        .unwrap();
+    assert_eq!(summary.count, 1);
+    assert_eq!(sequence.count(), 2);
+    assert_eq!(std::str::from_utf8(&first), Ok("<urlset><url><loc>http://www.example.com/</loc></url></urlset>"));
+    assert!(std::str::from_utf8(&second).unwrap().starts_with("<urlset xmlns="));
+    drop(sequence);
+    assert_eq!(names, ["sitemap-1.xml", "sitemap-2.xml"]);
+}
+
+#[test]
+fn test_write_sitemap_same_stream() {
+    let output = SiteMapWriter::new(Vec::<u8>::new())
+        .start_urlset()
+        .unwrap()
+        .end()
+        .unwrap()
+        .into_inner();
+    // Another document is written into the same stream only deliberately.
+    let (output, _) = SiteMapWriter::new(output)
+        .write_index_from_iter(Vec::<SiteMapEntry>::new())
+        .unwrap();
+    let output = String::from_utf8(output).unwrap();
+    assert_eq!(output.matches("<?xml").count(), 2);
+    assert!(output.contains("/><?xml"));
 }
 
 #[test]
@@ -81,3 +130,328 @@ fn test_validation() {
     assert!(UrlEntry::builder().priority(-1.0).build().is_err());
     assert!(SiteMapEntry::builder().build().is_err());
 }
+
+#[test]
+fn test_validation_errors() {
+    assert!(matches!(UrlEntry::builder().build(), Err(Error::MissingLocation)));
+    match UrlEntry::builder().loc("not a url").build() {
+        Err(Error::InvalidUrl { value, error }) => {
+            assert_eq!(value, "not a url");
+            assert_eq!(error, url::ParseError::RelativeUrlWithoutBase);
+        }
+        other => panic!("expected invalid url, got {:?}", other),
+    }
+    match UrlEntry::builder().loc("http://www.example.com/").priority(1.5).build() {
+        Err(Error::InvalidPriority { value }) => assert_eq!(value, "1.5"),
+        other => panic!("expected invalid priority, got {:?}", other),
+    }
+    let changefreq = ChangeFreq::from("sometimes".to_string());
+    match UrlEntry::builder().loc("http://www.example.com/").changefreq(changefreq).build() {
+        Err(Error::InvalidChangeFreq { value }) => assert_eq!(value, "sometimes"),
+        other => panic!("expected invalid changefreq, got {:?}", other),
+    }
+    let error = SiteMapEntry::builder().loc("http://").build().unwrap_err();
+    assert!(std::error::Error::source(&error).is_some());
+}
+
+#[test]
+fn test_write_sitemap_scope() {
+    let mut output = Vec::<u8>::new();
+    let scope = SiteMapScope::new(Url::parse("http://www.example.com/catalog/sitemap.xml").unwrap());
+    let sitemap_writer = SiteMapWriter::new(&mut output).with_scope(scope);
+    let mut urlwriter = sitemap_writer.start_urlset().expect("Can't write the file");
+    urlwriter.url("http://www.example.com/catalog/item.html").expect("url is in scope");
+    match urlwriter.url("http://www.example.com/index.html") {
+        Err(Error::ScopeViolation(url)) => {
+            assert_eq!(url.as_str(), "http://www.example.com/index.html");
+        }
+        other => panic!("expected scope violation, got {:?}", other),
+    }
+    assert!(urlwriter.url("https://www.example.com/catalog/item.html").is_err());
+    urlwriter.end().expect("close the urlset block");
+    let output = String::from_utf8(output).unwrap();
+    assert!(output.contains("http://www.example.com/catalog/item.html"));
+    assert!(!output.contains("http://www.example.com/index.html"));
+}
+
+#[test]
+fn test_write_sitemap_entries_limit() {
+    let mut output = Vec::<u8>::new();
+    let sitemap_writer = SiteMapWriter::new(&mut output);
+    let mut urlwriter = sitemap_writer.start_urlset().expect("Can't write the file");
+    for i in 0..MAX_ENTRIES {
+        urlwriter.url(format!("http://www.example.com/{}.html", i)).expect("Can't write the file");
+    }
+    assert_eq!(urlwriter.count(), MAX_ENTRIES);
+    assert_eq!(urlwriter.remaining_urls(), 0);
+    match urlwriter.url("http://www.example.com/last.html") {
+        Err(Error::LimitExceeded(Limit::Entries(max))) => assert_eq!(max, MAX_ENTRIES),
+        other => panic!("expected limit error, got {:?}", other),
+    }
+    urlwriter.end().expect("close the urlset block");
+    assert!(!String::from_utf8(output).unwrap().contains("last.html"));
+}
+
+#[test]
+fn test_write_sitemap_bytes_limit() {
+    let mut output = Vec::<u8>::new();
+    {
+        let sitemap_writer = SiteMapWriter::new(&mut output);
+        let mut sitemap_index_writer = sitemap_writer.start_sitemapindex()
+            .expect("start sitemap index tag");
+        let path = "a".repeat(2000);
+        let mut written = 0;
+        loop {
+            let loc = format!("http://www.example.com/{}/{}.xml", path, written);
+            let remaining = sitemap_index_writer.remaining_bytes();
+            match sitemap_index_writer.sitemap(loc) {
+                Ok(()) => {
+                    written += 1;
+                    assert!(sitemap_index_writer.remaining_bytes() < remaining);
+                }
+                Err(Error::LimitExceeded(Limit::Bytes(max))) => {
+                    assert_eq!(max, MAX_BYTES);
+                    assert!(remaining < 2100);
+                    break;
+                }
+                Err(error) => panic!("unexpected error {:?}", error),
+            }
+        }
+        assert_eq!(sitemap_index_writer.count(), written);
+        sitemap_index_writer.end().expect("close sitemap block");
+    }
+    assert!(output.len() <= MAX_BYTES);
+    assert!(output.len() > MAX_BYTES - 2100);
+}
+
+#[test]
+fn test_write_sitemap_stylesheet() {
+    let mut output = Vec::<u8>::new();
+    let config = SiteMapWriterConfig::new()
+        .stylesheet("/sitemap.xsl?a=1&b=2")
+        .comment("generator: sitemap -- test");
+    let sitemap_writer = SiteMapWriter::with_config(&mut output, config);
+    let mut urlwriter = sitemap_writer.start_urlset().expect("Can't write the file");
+    urlwriter.url("http://www.example.com/index.html").expect("Can't write the file");
+    urlwriter.end().expect("close the urlset block");
+    assert_eq!(std::str::from_utf8(&output).unwrap(),
+               "<?xml version=\"1.0\" encoding=\"UTF-8\"?>
+<?xml-stylesheet type=\"text/xsl\" href=\"/sitemap.xsl?a=1&amp;b=2\"?>
+<!-- generator: sitemap - - test -->
+<urlset xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\">
+  <url>
+    <loc>http://www.example.com/index.html</loc>
+  </url>
+</urlset>");
+}
+
+fn write_with_config(config: SiteMapWriterConfig) -> String {
+    let mut output = Vec::<u8>::new();
+    let sitemap_writer = SiteMapWriter::with_config(&mut output, config);
+    let mut urlwriter = sitemap_writer.start_urlset().expect("Can't write the file");
+    urlwriter.url("http://www.example.com/index.html").expect("Can't write the file");
+    urlwriter.end().expect("close the urlset block");
+    String::from_utf8(output).unwrap()
+}
+
+#[test]
+fn test_write_sitemap_output_config() {
+    assert_eq!(write_with_config(SiteMapWriterConfig::new().compact()),
+               "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\
+<urlset xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\">\
+<url><loc>http://www.example.com/index.html</loc></url>\
+</urlset>");
+    assert_eq!(write_with_config(SiteMapWriterConfig::new().indent("\t").line_separator("\r\n")),
+               "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\r
+<urlset xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\">\r
+\t<url>\r
+\t\t<loc>http://www.example.com/index.html</loc>\r
+\t</url>\r
+</urlset>");
+    assert_eq!(write_with_config(SiteMapWriterConfig::new().compact().xml_declaration(false)),
+               "<urlset xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\">\
+<url><loc>http://www.example.com/index.html</loc></url>\
+</urlset>");
+    let config = SiteMapWriterConfig::new()
+        .compact()
+        .xml_version(XmlVersion::Version11)
+        .standalone(true);
+    assert!(write_with_config(config)
+        .starts_with("<?xml version=\"1.1\" encoding=\"UTF-8\" standalone=\"yes\"?><urlset"));
+}
+
+/// Drops writers without `end()`, returns written urlset and sitemap index.
+fn write_unfinished(config: SiteMapWriterConfig) -> (String, String) {
+    let mut output = Vec::<u8>::new();
+    {
+        let sitemap_writer = SiteMapWriter::with_config(&mut output, config.clone().compact());
+        let mut urlwriter = sitemap_writer.start_urlset().unwrap();
+        urlwriter.url("http://www.example.com/index.html").unwrap();
+    }
+    let mut index_output = Vec::<u8>::new();
+    {
+        let sitemap_writer = SiteMapWriter::with_config(&mut index_output, config.compact());
+        let mut sitemap_index_writer = sitemap_writer.start_sitemapindex().unwrap();
+        sitemap_index_writer.sitemap("http://www.example.com/sitemap.xml").unwrap();
+    }
+    (String::from_utf8(output).unwrap(), String::from_utf8(index_output).unwrap())
+}
+
+#[test]
+fn test_write_sitemap_close_on_drop() {
+    let (urlset, index) = write_unfinished(SiteMapWriterConfig::new().close_on_drop(true));
+    assert!(urlset.ends_with("<url><loc>http://www.example.com/index.html</loc></url></urlset>"));
+    assert!(index.ends_with("<sitemap><loc>http://www.example.com/sitemap.xml</loc></sitemap></sitemapindex>"));
+
+    let (urlset, index) = write_unfinished(SiteMapWriterConfig::new());
+    assert!(!urlset.contains("</urlset>"));
+    assert!(!index.contains("</sitemapindex>"));
+}
+
+#[test]
+fn test_write_sitemap_finish() {
+    let sitemap_writer = SiteMapWriter::with_config(Vec::new(), SiteMapWriterConfig::new().compact());
+    let mut urlwriter = sitemap_writer.start_urlset().unwrap();
+    urlwriter.url("http://www.example.com/index.html").unwrap();
+    let output = urlwriter.finish().unwrap();
+    assert!(String::from_utf8(output).unwrap().ends_with("</url></urlset>"));
+
+    let sitemap_writer = SiteMapWriter::new(Vec::new());
+    let mut sitemap_index_writer = sitemap_writer.start_sitemapindex().unwrap();
+    sitemap_index_writer.sitemap("http://www.example.com/sitemap.xml").unwrap();
+    let output = sitemap_index_writer.finish().unwrap();
+    assert!(String::from_utf8(output).unwrap().ends_with("</sitemapindex>"));
+}
+
+#[test]
+fn test_write_sitemap_inner() {
+    let config = SiteMapWriterConfig::new().compact().xml_declaration(false);
+    let mut sitemap_writer = SiteMapWriter::with_config(Vec::new(), config);
+    sitemap_writer.get_mut().extend_from_slice(b"<!-- generated -->");
+    let mut urlwriter = sitemap_writer.start_urlset_without_ns().unwrap();
+    urlwriter.url("http://www.example.com/index.html").unwrap();
+    urlwriter.flush().unwrap();
+    assert_eq!(
+        String::from_utf8_lossy(urlwriter.get_ref()),
+        "<!-- generated --><urlset><url><loc>http://www.example.com/index.html</loc></url>",
+    );
+    urlwriter.get_mut().extend_from_slice(b"<!-- 1 url -->");
+    let sitemap_writer = urlwriter.end().unwrap();
+    assert_eq!(
+        String::from_utf8(sitemap_writer.into_inner()).unwrap(),
+        "<!-- generated --><urlset><url><loc>http://www.example.com/index.html</loc></url><!-- 1 url --></urlset>",
+    );
+
+    let mut sitemap_index_writer = SiteMapWriter::new(Vec::new()).start_sitemapindex().unwrap();
+    sitemap_index_writer.sitemap("http://www.example.com/sitemap.xml").unwrap();
+    assert!(String::from_utf8_lossy(sitemap_index_writer.get_ref()).ends_with("</sitemap>"));
+    sitemap_index_writer.get_mut().clear();
+    let output = sitemap_index_writer.finish().unwrap();
+    assert_eq!(String::from_utf8(output).unwrap(), "\n</sitemapindex>");
+}
+
+#[test]
+fn test_write_sitemap_from_iter() {
+    let config = SiteMapWriterConfig::new().compact().xml_declaration(false);
+    let urls = (1..=3).map(|index| format!("http://www.example.com/{}.html", index));
+    let (output, summary) = SiteMapWriter::with_config(Vec::new(), config.clone())
+        .write_urlset_from_iter(urls.map(UrlEntry::from))
+        .unwrap();
+    let output = String::from_utf8(output).unwrap();
+    assert_eq!(summary, WriteSummary { count: 3, bytes: output.len() });
+    assert!(output.starts_with("<urlset xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\"><url>"));
+    assert!(output.ends_with("<loc>http://www.example.com/3.html</loc></url></urlset>"));
+
+    let (output, summary) = SiteMapWriter::with_config(Vec::new(), config.clone())
+        .write_index_from_iter(vec!["http://www.example.com/1.xml", "http://www.example.com/2.xml"])
+        .unwrap();
+    assert_eq!(summary.count, 2);
+    assert!(String::from_utf8(output).unwrap().ends_with("<loc>http://www.example.com/2.xml</loc></sitemap></sitemapindex>"));
+
+    let (_, summary) = SiteMapWriter::new(Vec::new()).write_urlset_from_iter(Vec::<UrlEntry>::new()).unwrap();
+    assert_eq!(summary.count, 0);
+
+    #[derive(Debug)]
+    enum AppError {
+        Database,
+        Sitemap(Error),
+    }
+    impl From<Error> for AppError {
+        fn from(error: Error) -> AppError {
+            AppError::Sitemap(error)
+        }
+    }
+    let rows = vec![Ok("http://www.example.com/1.html"), Err(AppError::Database), Ok("http://www.example.com/2.html")];
+    let result = SiteMapWriter::new(Vec::new()).try_write_urlset_from_iter(rows);
+    assert!(matches!(result, Err(AppError::Database)));
+
+    let rows: Vec<Result<SiteMapEntry, AppError>> = vec![Ok(SiteMapEntry::from("http://www.example.com/1.xml"))];
+    let (_, summary) = SiteMapWriter::new(Vec::new()).try_write_index_from_iter(rows).unwrap();
+    assert_eq!(summary.count, 1);
+
+    let rows = vec![Ok("http://www.example.com/1.html"), Ok("http://www.example.com/2.html")];
+    let config = config.max_entries(1);
+    let result = SiteMapWriter::with_config(Vec::new(), config).try_write_urlset_from_iter(rows);
+    assert!(matches!(result, Err(AppError::Sitemap(Error::LimitExceeded(Limit::Entries(1))))));
+}
+
+#[test]
+fn test_write_sitemap_custom_limits() {
+    let config = SiteMapWriterConfig::new().max_entries(2).max_bytes(250);
+    let mut urlwriter = SiteMapWriter::with_config(Vec::new(), config).start_urlset().unwrap();
+    assert_eq!(urlwriter.remaining_urls(), 2);
+    urlwriter.url("http://www.example.com/1.html").unwrap();
+    assert_eq!(urlwriter.remaining_urls(), 1);
+    let result = urlwriter.url("http://www.example.com/long/path/to/the/second/page.html");
+    assert!(matches!(result, Err(Error::LimitExceeded(Limit::Bytes(250)))));
+    urlwriter.finish().unwrap();
+
+    let config = SiteMapWriterConfig::new().max_entries(MAX_ENTRIES + 1);
+    let urlwriter = SiteMapWriter::with_config(Vec::new(), config).start_urlset().unwrap();
+    assert_eq!(urlwriter.remaining_urls(), MAX_ENTRIES);
+    urlwriter.finish().unwrap();
+}
+
+fn read_back(output: &[u8]) -> Vec<UrlEntry> {
+    SiteMapReader::new(output)
+        .map(|entity| match entity {
+            SiteMapEntity::Url(url) => url,
+            other => panic!("expected url, got {:?}", other),
+        })
+        .collect()
+}
+
+#[test]
+fn test_write_sitemap_escaping() {
+    let urls = [
+        UrlEntry::from("http://www.example.com/it's?a=1&b=<2>&c=\"3\""),
+        UrlEntry::from("http://www.example.com/a&b'c"),
+    ];
+    let config = SiteMapWriterConfig::new().compact().xml_declaration(false);
+    let (output, _) = SiteMapWriter::with_config(Vec::new(), config)
+        .write_urlset_from_iter(urls.clone())
+        .unwrap();
+    let text = String::from_utf8(output.clone()).unwrap();
+    assert!(text.contains("<loc>http://www.example.com/it&apos;s?a=1&amp;b=%3C2%3E&amp;c=%223%22</loc>"), "{}", text);
+    assert!(text.contains("<loc>http://www.example.com/a&amp;b&apos;c</loc>"), "{}", text);
+    assert_eq!(read_back(&output), urls);
+}
+
+#[test]
+fn test_write_sitemap_iri() {
+    let url = UrlEntry::from("http://пример.рф/путь/%2F?q=значение#фрагмент");
+    let (output, _) = SiteMapWriter::new(Vec::new())
+        .write_urlset_from_iter([url.clone()])
+        .unwrap();
+    let text = String::from_utf8(output.clone()).unwrap();
+    assert!(text.contains("<loc>http://xn--e1afmkfd.xn--p1ai/%D0%BF%D1%83%D1%82%D1%8C/%2F?q="), "{}", text);
+    assert_eq!(read_back(&output), std::slice::from_ref(&url));
+
+    let config = SiteMapWriterConfig::new().iri_policy(IriPolicy::Unicode);
+    let (output, _) = SiteMapWriter::with_config(Vec::new(), config)
+        .write_urlset_from_iter([url.clone()])
+        .unwrap();
+    let text = String::from_utf8(output.clone()).unwrap();
+    assert!(text.contains("<loc>http://пример.рф/путь/%2F?q=значение#фрагмент</loc>"), "{}", text);
+    assert_eq!(read_back(&output), [url]);
+}